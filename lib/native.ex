@@ -27,9 +27,64 @@ defmodule RustlerBtleplug.Native do
   @type central() :: reference()
   @type peripheral() :: reference()
   @type gatt_peripheral() :: reference()
+  @type op_ref() :: reference()
+  @type scan_options() :: %{optional(:services) => [uuid()], optional(:continuous) => boolean()}
+  @type scan_status() :: %{
+          scanning: boolean(),
+          session_id: non_neg_integer() | nil,
+          continuous: boolean(),
+          duration_ms: non_neg_integer() | nil,
+          started_at: integer() | nil
+        }
+  @type service_discovery_policy() :: :never | :always | {:allow_listed, [uuid() | mac()]}
+  @type update_throttle() :: %{
+          optional(:min_interval_ms) => non_neg_integer(),
+          optional(:rssi_delta) => non_neg_integer()
+        }
+  @type central_options() :: %{
+          optional(:service_discovery) => service_discovery_policy(),
+          optional(:update_throttle) => update_throttle() | nil,
+          optional(:scan_report_interval_ms) => pos_integer(),
+          optional(:advertisement_format) => :struct | :map,
+          optional(:decode_beacons) => boolean(),
+          optional(:decode_bthome) => boolean(),
+          optional(:adapter) => adapter_selector(),
+          optional(:adapters) => :all | [adapter_selector()]
+        }
+  @type adapter_selector() :: non_neg_integer() | String.t()
+  @type adapter_state() :: :powered_on | :powered_off | :unknown
+  @type adapter_listing() :: %{index: non_neg_integer(), info: String.t()}
+  @type throttle_stats() :: %{
+          total_suppressed: non_neg_integer(),
+          suppressed: %{uuid() => non_neg_integer()}
+        }
+  @type advertisement_filter() :: %{
+          optional(:name_prefix) => String.t(),
+          optional(:name_regex) => String.t(),
+          optional(:min_rssi) => integer(),
+          optional(:manufacturer_ids) => [non_neg_integer()],
+          optional(:service_data_uuids) => [uuid()],
+          optional(:allow_addresses) => [mac() | uuid()],
+          optional(:deny_addresses) => [mac() | uuid()]
+        }
   @type uuid() :: String.t()
   @type mac() :: String.t()
   @type state_graph() :: String.t()
+  @type write_type() :: :with_response | :without_response
+  @typedoc """
+  A characteristic UUID, optionally qualified by its service UUID. Use the
+  `{service, characteristic}` form when a device reuses a characteristic UUID
+  in several services, otherwise `{:error, {:ambiguous_characteristic, candidates}}`
+  is returned.
+  """
+  @type characteristic() :: uuid() | {uuid() | nil, uuid()}
+  @typedoc """
+  Packed payload layout, fields in wire order. Types are `:u8`, `:i8`, `:u16le`,
+  `:i16be`, ... up to 64 bits, `:f32le` / `:f64be`, `{:bitfield, [flag]}`,
+  `{:string, length}` and `{:bytes, length}` (up to 512 bytes). `scale` multiplies
+  numeric values on decode and divides them on encode.
+  """
+  @type payload_schema() :: [{atom(), term()} | {atom(), term(), [scale: number()]}]
   # @type state_map() :: %{
   #         adapter: %RustlerBtleplug.AdapterInfo{},
   #         peripherals: %{uuid() => %RustlerBtleplug.PeripheralInfo{}},
@@ -43,15 +98,147 @@ defmodule RustlerBtleplug.Native do
   @spec init(map()) :: {:ok, central()} | {:error, term()}
   def init(_opts \\ %{}), do: error()
 
-  @spec create_central(Pid.t()) :: {:ok, central()} | {:error, term()}
-  def create_central(_pid \\ self()), do: error()
+  @doc """
+  Create a central manager sending its events to `pid`.
+
+  Options:
+    - `service_discovery` - GATT service discovery for devices seen while scanning:
+      `:never` (default), `:always` (previous behaviour) or
+      `{:allow_listed, [peripheral_id_or_address]}`.
+    - `update_throttle` - throttle `btleplug_peripheral_updated` per device, see
+      `set_update_throttle/2`. Off by default.
+    - `scan_report_interval_ms` - batch mode. Instead of one message per advertisement,
+      send one `{:btleplug_scan_report, [entry]}` per window with the latest state of
+      every device seen in it. Entries are atom-keyed maps with the
+      `RustlerBtleplug.Advertisement` fields (integer or `nil` `rssi`) plus `id` and
+      `last_seen` (µs since the Unix epoch).
+    - `advertisement_format` - properties in `btleplug_peripheral_discovered` /
+      `btleplug_peripheral_updated`: `:struct` (default) sends a
+      `%RustlerBtleplug.Advertisement{}`, `:map` the previous string-keyed map
+      with stringified numbers. Both carry the `adapter` index that heard the device.
+    - `decode_beacons` - send `{:btleplug_beacon, peripheral_id, beacon}` for iBeacon,
+      AltBeacon and Eddystone frames, next to the raw advertisement messages.
+      See `decode_beacon/2` for the beacon fields, `rssi` is added.
+    - `decode_bthome` - send `{:btleplug_sensor_reading, peripheral_id, reading}` for
+      BTHome v2 service data (`0xFCD2`), see `decode_bthome/3`. Encrypted frames need
+      a bindkey registered with `set_bthome_bindkey/3`.
+    - `adapter` - radio to use, an index or a case-insensitive substring of the
+      adapter info from `list_adapters/0`. Defaults to the first adapter. Fails with
+      `{:error, {:adapter_not_found, adapter, [info]}}` when nothing matches.
+    - `adapters` - `:all` or a list of selectors as for `adapter`. Events of every
+      adapter go to `pid`, scans run on all of them and `find_peripheral/3` uses the
+      adapter hearing the device best. Takes precedence over `adapter`.
+
+  When an adapter disappears (USB dongle unplugged, bluetoothd restarted) `pid` gets
+  `{:btleplug_adapter_lost, %{index: index, info: info}}`. The central keeps looking
+  for it with backoff, re-attaches to the same adapter or the next one matching the
+  selector, resumes a running scan and sends `{:btleplug_adapter_recovered, adapter}`.
+  """
+  @spec create_central(pid(), central_options()) :: central() | {:error, term()}
+  def create_central(_pid \\ self(), _opts \\ %{}), do: error()
+
+  @doc """
+  List the Bluetooth adapters as `[%{index: 0, info: "hci0 (usb:v1D6Bp0246d0540)"}]`.
+  """
+  @spec list_adapters() :: [adapter_listing()] | {:error, term()}
+  def list_adapters(), do: error()
+
+  @doc """
+  Last RSSI of a device as heard by each adapter of the central,
+  `%{adapter_index => rssi}`. The device is given by peripheral id or BD address;
+  readings are kept per address, so the ids each adapter uses for it all resolve
+  to the same map.
+  """
+  @spec adapter_rssi(central(), uuid() | String.t()) ::
+          %{non_neg_integer() => integer()} | {:error, term()}
+  def adapter_rssi(_central, _device), do: error()
+
+  @doc """
+  Power state of the central's adapters. `:powered_on` as soon as one adapter is
+  usable, `:powered_off` when all are off. Changes are also sent as
+  `{:btleplug_adapter_status_update, state}` with the state of the adapter that changed.
+  """
+  @spec adapter_state(central()) :: adapter_state() | {:error, term()}
+  def adapter_state(_central), do: error()
+
+  @doc """
+  Block until `adapter_state/1` is `state`, e.g. `:powered_on` at start-up.
+  Returns `state`, or `{:error, :timeout}` after `timeout` ms.
+  """
+  @spec wait_for_adapter_state(central(), adapter_state(), non_neg_integer()) ::
+          adapter_state() | {:error, term()}
+  def wait_for_adapter_state(_central, _state, _timeout \\ @default_timeout), do: error()
+
+  @doc """
+  Start scanning for `ms` milliseconds.
+
+  Every scan gets a session id. The owner receives
+  `{:btleplug_scan_started, %{session_id: id, continuous: bool, duration_ms: ms | nil, started_at: us}}`
+  and later `{:btleplug_scan_stopped, %{session_id: id, reason: reason}}` with reason
+  `:timeout`, `:stopped`, `:replaced` (a newer `start_scan`) or `:error`.
+  A running scan is replaced by a new `start_scan`, its timer never stops the new one.
+
+  Options:
+    - `services` - list of service UUIDs (full or 16-bit short form like `"180d"`).
+      Only peripherals advertising one of them are reported.
+    - `continuous` - scan until `stop_scan/1`, `ms` is ignored.
+  """
+  @spec start_scan(central(), number(), scan_options()) :: central() | {:error, term()}
+  def start_scan(_central, _ms \\ 1000, _opts \\ %{}), do: error()
+
+  @doc """
+  Replace the advertisement filter of a central, also while a scan is running.
+
+  Discovery, update and advertisement messages are only sent for peripherals
+  matching every configured criterion. Pass `%{}` to clear the filter.
+
+  Filter keys:
+    - `name_prefix` - local name has to start with this string
+    - `name_regex` - local name has to match this regex
+    - `min_rssi` - minimum RSSI in dBm
+    - `manufacturer_ids` - list of company IDs, one of them must be advertised
+    - `service_data_uuids` - list of service UUIDs, service data for one of them must be advertised
+    - `allow_addresses` - only these addresses / peripheral ids pass
+    - `deny_addresses` - these addresses / peripheral ids never pass
+  """
+  @spec set_advertisement_filter(central(), advertisement_filter()) ::
+          central() | {:error, term()}
+  def set_advertisement_filter(_central, _filter), do: error()
 
-  @spec start_scan(central(), number()) :: {:ok, central()} | {:error, term()}
-  def start_scan(_central, _ms \\ 1000), do: error()
+  @doc """
+  Replace the `btleplug_peripheral_updated` throttle of a central, `nil` turns it off.
+
+  Updates whose name, manufacturer data and service data are unchanged are sent
+  once `min_interval_ms` passed since the last update of the device, or earlier when
+  the RSSI moved by at least `rssi_delta` dBm (0 disables that). Changed payloads
+  always pass.
+  """
+  @spec set_update_throttle(central(), update_throttle() | nil) :: central() | {:error, term()}
+  def set_update_throttle(_central, _throttle), do: error()
+
+  @doc """
+  Number of updates dropped by the throttle, in total and per peripheral.
+  """
+  @spec update_throttle_stats(central()) :: throttle_stats() | {:error, term()}
+  def update_throttle_stats(_central), do: error()
+
+  @doc """
+  Register the BTHome bindkey (32 hex characters or 16 bytes) of a device by
+  address or peripheral id. `nil` removes it.
+  """
+  @spec set_bthome_bindkey(central(), mac() | uuid(), String.t() | binary() | nil) ::
+          central() | {:error, term()}
+  def set_bthome_bindkey(_central, _address, _bindkey), do: error()
 
   @spec stop_scan(central()) :: {:ok, central()} | {:error, term()}
   def stop_scan(_central), do: error()
 
+  @doc """
+  Report whether a scan session is running on this central.
+  """
+  @spec scan_status(central()) :: scan_status() | {:error, term()}
+  def scan_status(_central), do: error()
+
   ## ✅ Peripheral Discovery
   @doc """
   Find a peripheral by UUID.
@@ -68,19 +255,234 @@ defmodule RustlerBtleplug.Native do
   def find_peripheral_by_name(_central, _name, _timeout \\ @default_timeout), do: error()
 
   ## ✅ Peripheral Connection
-  @spec connect(peripheral(), number()) :: {:ok, peripheral()} | {:error, term()}
+  @doc """
+  Connect to a peripheral asynchronously.
+
+  Returns an operation reference right away. The pid owning the peripheral
+  receives `{:btleplug_op_result, ref, :ok | {:error, reason}}` once the
+  connection attempt has finished. `disconnect/2`, `subscribe/3` and
+  `unsubscribe/3` report their outcome the same way.
+  """
+  @spec connect(peripheral(), number()) :: op_ref() | {:error, term()}
   def connect(_peripheral, _timeout \\ @default_timeout), do: error()
 
-  @spec disconnect(peripheral(), number()) :: {:ok, peripheral()} | {:error, term()}
+  @doc """
+  Connect to a device by address (or peripheral id) without scanning first.
+
+  Known devices are connected right away (reusing their peripheral reference),
+  otherwise a scan runs for up to `scan_timeout` ms until the address shows up.
+  A running `start_scan/3` session is kept as is, its filter included.
+  Returns an operation reference;
+  the central's pid receives `{:btleplug_op_progress, ref, phase}` for each phase:
+  `:scanning`, `{:found, peripheral}` and `:connecting`, then
+  `{:btleplug_op_result, ref, :ok | {:error, reason}}`. `reason` is
+  `:peripheral_not_found` when the scan ends without a match.
+  """
+  @spec connect_by_address(central(), mac() | uuid(), number(), number()) ::
+          op_ref() | {:error, term()}
+  def connect_by_address(
+        _central,
+        _address,
+        _scan_timeout \\ 5000,
+        _timeout \\ @default_timeout
+      ),
+      do: error()
+
+  @spec disconnect(peripheral(), number()) :: op_ref() | {:error, term()}
   def disconnect(_peripheral, _timeout \\ @default_timeout), do: error()
 
   ## ✅ Notifications & Subscriptions
-  @spec subscribe(peripheral(), uuid(), number()) :: {:ok, peripheral()} | {:error, term()}
+  @doc """
+  Subscribe to notifications/indications of a characteristic.
+
+  A single notification stream is shared by all subscriptions of a peripheral,
+  so every value change is delivered once. The stream stops when the last
+  characteristic is unsubscribed or the peripheral disconnects.
+  """
+  @spec subscribe(peripheral(), characteristic(), number()) :: op_ref() | {:error, term()}
   def subscribe(_peripheral, _characteristic, _timeout \\ @default_timeout), do: error()
 
-  @spec unsubscribe(peripheral(), uuid(), number()) :: {:ok, peripheral()} | {:error, term()}
+  @spec unsubscribe(peripheral(), characteristic(), number()) :: op_ref() | {:error, term()}
   def unsubscribe(_peripheral, _characteristic, _timeout \\ @default_timeout), do: error()
 
+  ## ✅ Characteristic Read / Write
+  @doc """
+  Read the current value of a characteristic.
+
+  Returns the value as a binary, or `{:error, reason}` where reason is one of
+  `:service_not_found`, `:characteristic_not_found`, `:not_readable`, `:timeout`,
+  `:service_discovery_failed`, `{:ambiguous_characteristic, candidates}` or
+  `{:btleplug_error, message}`.
+
+  With `set_value_decoding/2` enabled, a registered payload schema or a
+  Presentation Format descriptor (0x2904) on the characteristic the result is
+  `{value, decoded}`, see `decode_characteristic_value/2`, `register_payload_schema/4`
+  and `apply_presentation_format/2`.
+  """
+  @spec read(peripheral(), characteristic(), number()) ::
+          binary() | {binary(), map() | nil} | {:error, term()}
+  def read(_peripheral, _characteristic, _timeout \\ @default_timeout), do: error()
+
+  @doc """
+  Decode values of standard characteristics (heart rate, battery, temperature,
+  CSC/RSC, weight, blood pressure) on this peripheral. Notifications get the
+  decoded map in `meta.decoded` and `read/3` returns `{value, decoded}`.
+  Unknown characteristics decode to `nil`.
+  """
+  @spec set_value_decoding(peripheral(), boolean()) :: peripheral() | {:error, term()}
+  def set_value_decoding(_peripheral, _enabled), do: error()
+
+  @doc """
+  Register the payload schema of a characteristic, `nil` removes it.
+
+  Notifications of the characteristic get the unpacked map in `meta.decoded`,
+  `read/3` returns `{value, decoded}` and `write/5` accepts a map instead of a binary.
+  A registered schema takes precedence over `set_value_decoding/2`.
+  """
+  @spec register_payload_schema(peripheral(), uuid(), uuid(), payload_schema() | nil) ::
+          peripheral() | {:error, term()}
+  def register_payload_schema(_peripheral, _service, _characteristic, _schema), do: error()
+
+  @doc """
+  Write a binary payload to a characteristic.
+
+  `write_type` is `:with_response` (requires the `Write` property) or
+  `:without_response` (requires `Write Without Response`). Returns `:ok` or
+  `{:error, reason}`.
+
+  With a registered payload schema `data` may be a map, which fails with
+  `:no_payload_schema`, `{:missing_field, name}` or `{:invalid_field, name}`.
+  """
+  @spec write(peripheral(), characteristic(), binary() | map(), write_type(), number()) ::
+          :ok | {:error, term()}
+  def write(
+        _peripheral,
+        _characteristic,
+        _data,
+        _write_type \\ :with_response,
+        _timeout \\ @default_timeout
+      ),
+      do: error()
+
+  ## ✅ Descriptor Read / Write
+  @doc """
+  Read a descriptor value, addressed by its (service, characteristic, descriptor)
+  UUID triple, e.g. the CCCD `00002902-0000-1000-8000-00805f9b34fb`.
+  """
+  @spec read_descriptor(peripheral(), uuid(), uuid(), uuid(), number()) ::
+          binary() | {:error, term()}
+  def read_descriptor(
+        _peripheral,
+        _service,
+        _characteristic,
+        _descriptor,
+        _timeout \\ @default_timeout
+      ),
+      do: error()
+
+  @doc """
+  Write a descriptor value, addressed by its (service, characteristic, descriptor)
+  UUID triple. Returns `:ok` or `{:error, reason}`.
+  """
+  @spec write_descriptor(peripheral(), uuid(), uuid(), uuid(), binary(), number()) ::
+          :ok | {:error, term()}
+  def write_descriptor(
+        _peripheral,
+        _service,
+        _characteristic,
+        _descriptor,
+        _data,
+        _timeout \\ @default_timeout
+      ),
+      do: error()
+
+  ## ✅ Advertisement Decoding
+  @doc """
+  Decode a beacon frame, `source` is the company ID for manufacturer data or the
+  service UUID for service data. Returns `nil` for anything that isn't a beacon.
+
+    - iBeacon / AltBeacon: `%{type: :ibeacon | :altbeacon, uuid, major, minor, measured_power}`
+      (AltBeacon adds `company_id` and `manufacturer_reserved`)
+    - Eddystone UID: `%{type: :eddystone_uid, tx_power, namespace, instance}` (hex strings)
+    - Eddystone URL: `%{type: :eddystone_url, tx_power, url}`
+    - Eddystone TLM: `%{type: :eddystone_tlm, battery_mv, temperature, adv_count, uptime_ms}`
+    - Eddystone EID: `%{type: :eddystone_eid, tx_power, eid}`
+  """
+  @spec decode_beacon(non_neg_integer() | uuid(), binary()) :: map() | nil
+  def decode_beacon(_source, _data), do: error()
+
+  @doc """
+  Decode BTHome v2 service data into
+  `%{format: :bthome, encrypted, trigger_based, packet_id, measurements: [%{name, value, unit}]}`.
+
+  Numeric values are scaled (`21.5`, unit `"°C"`), binary sensors are booleans and
+  button events atoms like `:press` or `:long_press`. Encrypted frames need the
+  device `address` (part of the nonce) and its `bindkey`.
+  """
+  @spec decode_bthome(binary(), mac() | nil, String.t() | binary() | nil) ::
+          map() | {:error, term()}
+  def decode_bthome(_data, _address \\ nil, _bindkey \\ nil), do: error()
+
+  @doc """
+  Decode the value of a standard characteristic into `%{type: ..., ...fields}`,
+  `nil` for characteristics without a decoder or truncated values.
+
+    - `:battery_level` (0x2A19): `battery_level` in %
+    - `:heart_rate_measurement` (0x2A37): `heart_rate`, `sensor_contact`,
+      `energy_expended_kj`, `rr_intervals_ms`
+    - `:temperature_measurement` (0x2A1C, 0x2A1E): `temperature`, `unit`, `timestamp`,
+      `temperature_type`
+    - `:csc_measurement` (0x2A5B): cumulative revolutions and event times (1/1024 s)
+    - `:rsc_measurement` (0x2A53): `speed_mps`, `cadence`, `stride_length_m`,
+      `total_distance_m`, `running`
+    - `:weight_measurement` (0x2A9D): `weight`, `unit`, `bmi`, `height`, `height_unit`, ...
+    - `:blood_pressure_measurement` (0x2A35, 0x2A36): `systolic`, `diastolic`,
+      `mean_arterial_pressure`, `unit`, `pulse_rate`, ...
+
+  Timestamps are Erlang datetime tuples, IEEE-11073 special values (NaN, NRes, ±INF)
+  decode to `nil`.
+  """
+  @spec decode_characteristic_value(uuid(), binary()) :: map() | nil
+  def decode_characteristic_value(_characteristic_uuid, _data), do: error()
+
+  @doc """
+  Unpack a payload with a schema, `nil` when it is shorter than the schema.
+  """
+  @spec decode_payload(payload_schema(), binary()) :: map() | nil
+  def decode_payload(_schema, _data), do: error()
+
+  @doc """
+  Pack a map with a schema, the inverse of `decode_payload/2`.
+  """
+  @spec encode_payload(payload_schema(), map()) :: binary() | {:error, term()}
+  def encode_payload(_schema, _values), do: error()
+
+  @doc """
+  Scale a value with a raw Characteristic Presentation Format descriptor (0x2904)
+  into `%{value: number, unit: atom | nil}`, e.g. `%{value: 21.5, unit: :degree_celsius}`.
+
+  Formats are read on connect and applied to reads and notifications automatically,
+  after payload schemas and standard decoders. Non-numeric formats return `nil`.
+  """
+  @spec apply_presentation_format(binary(), binary()) ::
+          %{value: number(), unit: atom() | nil} | nil
+  def apply_presentation_format(_descriptor, _value), do: error()
+
+  @doc """
+  Look up a name in the Bluetooth SIG assigned numbers. Integers are company IDs
+  unless `kind` is `:appearance` or `:unit`, UUIDs (short `"180d"` or full) are tried as
+  service, characteristic and descriptor unless `kind` narrows it down.
+
+      lookup_assigned_number("180d")          #=> {:service, "Heart Rate"}
+      lookup_assigned_number(0x004C)          #=> {:company, "Apple, Inc."}
+      lookup_assigned_number(0x03C1, :appearance) #=> {:appearance, "Keyboard"}
+  """
+  @spec lookup_assigned_number(
+          non_neg_integer() | uuid(),
+          :service | :characteristic | :descriptor | :company | :appearance | :unit | nil
+        ) :: {atom(), String.t()} | nil
+  def lookup_assigned_number(_value, _kind \\ nil), do: error()
+
   ## ✅ Adapter State Queries (Graph & Mindmap)
   @doc """
   Retrieve the adapter state as a **GraphViz** or **Mermaid mindmap**.
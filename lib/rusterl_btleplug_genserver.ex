@@ -27,6 +27,7 @@ defmodule RustlerBtleplug.Genserver do
      %{
        central: nil,
        peripheral: nil,
+       pending_ops: %{},
        messages: []
      }}
   end
@@ -50,15 +51,33 @@ defmodule RustlerBtleplug.Genserver do
     {:noreply, new_state}
   end
 
-  def handle_info({:btleplug_characteristic_value_changed, uuid, value}, state) do
-    Logger.debug("NIF Characteristic Value Changed for #{uuid}: #{value}")
+  def handle_info({:btleplug_characteristic_value_changed, uuid, value, meta}, state) do
+    Logger.debug(
+      "NIF Characteristic Value Changed for #{meta.peripheral_id} #{uuid}: #{inspect(value)}"
+    )
 
     new_state =
-      update_state_with_message(state, {:btleplug_characteristic_value_changed, uuid, value})
+      update_state_with_message(
+        state,
+        {:btleplug_characteristic_value_changed, uuid, value, meta}
+      )
 
     {:noreply, new_state}
   end
 
+  def handle_info({:btleplug_op_result, ref, result}, state) do
+    Logger.debug("NIF Operation Result for #{inspect(ref)}: #{inspect(result)}")
+
+    case Map.pop(state.pending_ops, ref) do
+      {nil, _pending_ops} ->
+        {:noreply, update_state_with_message(state, {:btleplug_op_result, ref, result})}
+
+      {from, pending_ops} ->
+        GenServer.reply(from, result)
+        {:noreply, %{state | pending_ops: pending_ops}}
+    end
+  end
+
   def handle_info(msg, state) do
     Logger.debug("NIF msg received : #{inspect(msg)}")
     new_state = update_state_with_message(state, msg)
@@ -183,40 +202,40 @@ defmodule RustlerBtleplug.Genserver do
     end
   end
 
-  def handle_call({:connect, _peripheral_ref}, _from, state) do
+  def handle_call({:connect}, from, state) do
     case state.peripheral do
       nil ->
         Logger.debug("No peripheral reference to connect.")
-        {:noreply, state}
+        {:reply, {:error, :no_peripheral}, state}
 
       peripheral_ref ->
         case RustlerBtleplug.Native.connect(peripheral_ref) do
           {:error, reason} ->
-            Logger.debug("Failed to connect to #{inspect(peripheral_ref)}: #{reason}")
-            {:noreply, state}
+            Logger.debug("Failed to connect to #{inspect(peripheral_ref)}: #{inspect(reason)}")
+            {:reply, {:error, reason}, state}
 
-          peripheral_ref ->
+          op_ref ->
             Logger.debug("Connecting to #{inspect(peripheral_ref)}")
-            {:reply, {:ok, peripheral_ref}, %{state | peripheral: peripheral_ref}}
+            {:noreply, %{state | pending_ops: Map.put(state.pending_ops, op_ref, from)}}
         end
     end
   end
 
-  def handle_call({:subscribe, uuid}, _from, state) do
+  def handle_call({:subscribe, uuid}, from, state) do
     case state.peripheral do
       nil ->
         Logger.debug("No peripheral reference to subscribe to.")
-        {:noreply, state}
+        {:reply, {:error, :no_peripheral}, state}
 
       peripheral_ref ->
         case RustlerBtleplug.Native.subscribe(peripheral_ref, uuid) do
           {:error, reason} ->
-            Logger.debug("Failed to subscribe to #{uuid}: #{reason}")
-            {:noreply, state}
+            Logger.debug("Failed to subscribe to #{inspect(uuid)}: #{inspect(reason)}")
+            {:reply, {:error, reason}, state}
 
-          peripheral_ref ->
-            Logger.debug("Subscribing to #{uuid} #{inspect(peripheral_ref)}")
-            {:reply, {:ok, peripheral_ref}, %{state | peripheral: peripheral_ref}}
+          op_ref ->
+            Logger.debug("Subscribing to #{inspect(uuid)} #{inspect(peripheral_ref)}")
+            {:noreply, %{state | pending_ops: Map.put(state.pending_ops, op_ref, from)}}
         end
     end
   end
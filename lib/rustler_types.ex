@@ -9,7 +9,16 @@ end
 defmodule RustlerBtleplug.PeripheralInfo do
   @moduledoc false
   @enforce_keys [:id, :name, :rssi, :tx_power, :services]
-  defstruct [:id, :name, :rssi, :rssi_cache, :is_connected, :tx_power, :services]
+  defstruct [
+    :id,
+    :name,
+    :rssi,
+    :rssi_cache,
+    :is_connected,
+    :tx_power,
+    :services,
+    manufacturers: []
+  ]
 
   @type t :: %__MODULE__{
           id: String.t(),
@@ -18,6 +27,8 @@ defmodule RustlerBtleplug.PeripheralInfo do
           rssi_cache: [{integer(), integer()}], # Tuple: {timestamp, RSSI value}
           is_connected: boolean(),
           tx_power: integer() | nil,
+          # company names from the manufacturer data, "0x1234" when unknown
+          manufacturers: [String.t()],
           services: [RustlerBtleplug.ServiceInfo.t()]
         }
 end
@@ -25,10 +36,11 @@ end
 defmodule RustlerBtleplug.ServiceInfo do
   @moduledoc false
   @enforce_keys [:uuid, :characteristics]
-  defstruct [:uuid, :characteristics]
+  defstruct [:uuid, :name, :characteristics]
 
   @type t :: %__MODULE__{
           uuid: String.t(),
+          name: String.t() | nil,
           characteristics: [RustlerBtleplug.CharacteristicInfo.t()]
         }
 end
@@ -36,10 +48,52 @@ end
 defmodule RustlerBtleplug.CharacteristicInfo do
   @moduledoc false
   @enforce_keys [:uuid, :properties]
-  defstruct [:uuid, :properties]
+  defstruct [:uuid, :name, :properties, descriptors: []]
 
   @type t :: %__MODULE__{
           uuid: String.t(),
-          properties: [String.t()]
+          name: String.t() | nil,
+          properties: [String.t()],
+          descriptors: [RustlerBtleplug.DescriptorInfo.t()]
+        }
+end
+
+defmodule RustlerBtleplug.DescriptorInfo do
+  @moduledoc false
+  @enforce_keys [:uuid]
+  defstruct [:uuid, :name]
+
+  @type t :: %__MODULE__{uuid: String.t(), name: String.t() | nil}
+end
+
+defmodule RustlerBtleplug.Advertisement do
+  @moduledoc false
+  defstruct [
+    :address,
+    :address_type,
+    :local_name,
+    :tx_power_level,
+    :rssi,
+    :class,
+    :adapter,
+    manufacturer_data: %{},
+    service_data: %{},
+    services: []
+  ]
+
+  @type t :: %__MODULE__{
+          address: String.t(),
+          address_type: :public | :random | nil,
+          local_name: String.t() | nil,
+          tx_power_level: integer() | nil,
+          rssi: integer() | nil,
+          class: non_neg_integer() | nil,
+          # list_adapters/0 index of the adapter that heard it
+          adapter: non_neg_integer() | nil,
+          # company id => payload
+          manufacturer_data: %{non_neg_integer() => binary()},
+          # service uuid => payload
+          service_data: %{String.t() => binary()},
+          services: [String.t()]
         }
 end
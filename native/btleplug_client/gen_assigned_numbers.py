@@ -0,0 +1,233 @@
+#!/usr/bin/env python3
+"""Regenerate the company identifier and appearance tables in src/assigned_numbers.rs
+from the Bluetooth SIG assigned numbers YAML.
+
+    python3 gen_assigned_numbers.py                 # fetch the published YAML
+    python3 gen_assigned_numbers.py <public repo>   # or use a local checkout
+
+Where the SIG repository is out of reach, each table can come from a published
+machine-readable copy of the same list instead:
+
+    --companies-json <bluetooth-numbers-database>/v1/company_ids.json
+    --appearance-rs  <btuuid>/src/appearance/categories.rs
+
+Only the blocks between the `// generated:` markers are rewritten, each headed by a
+`// Source:` line recording the input and its revision.
+"""
+
+import argparse
+import datetime
+import hashlib
+import json
+import os
+import re
+import subprocess
+import sys
+import urllib.request
+
+SIG_REPO = "https://bitbucket.org/bluetooth-SIG/public/raw/main"
+COMPANIES_YAML = "assigned_numbers/company_identifiers/company_identifiers.yaml"
+APPEARANCE_YAML = "assigned_numbers/core/appearance_values.yaml"
+TARGET = os.path.join(os.path.dirname(os.path.abspath(__file__)), "src", "assigned_numbers.rs")
+
+ITEM = re.compile(r"^(\s*)(- )?(\w+):\s*(.*?)\s*$")
+
+
+def fetch(source, path):
+    if os.path.isdir(source):
+        with open(os.path.join(source, path), encoding="utf-8", newline="") as f:
+            return f.read()
+    with urllib.request.urlopen(f"{source}/{path}") as response:
+        return response.read().decode("utf-8")
+
+
+def read(path):
+    with open(path, encoding="utf-8", newline="") as f:
+        return f.read()
+
+
+def sha256(text):
+    return hashlib.sha256(text.encode("utf-8")).hexdigest()[:12]
+
+
+def sig_revision(source):
+    """Commit of a local checkout, or the branch and fetch date of the published YAML."""
+    if os.path.isdir(source):
+        result = subprocess.run(
+            ["git", "-C", source, "rev-parse", "HEAD"], capture_output=True, text=True
+        )
+        if result.returncode == 0:
+            return f"commit {result.stdout.strip()}"
+        return "local copy"
+    return f"main as of {datetime.date.today().isoformat()}"
+
+
+def scalar(value):
+    if value.startswith("'") and value.endswith("'"):
+        return value[1:-1].replace("''", "'")
+    if value.startswith('"') and value.endswith('"'):
+        return value[1:-1].replace('\\"', '"').replace("\\\\", "\\")
+    return value
+
+
+def entries(text):
+    """The flat `- key: value` mappings of a SIG YAML list, nested lists included."""
+    current = None
+    for line in text.splitlines():
+        match = ITEM.match(line)
+        if not match or line.lstrip().startswith("#"):
+            continue
+        _, dash, key, value = match.groups()
+        if dash:
+            if current:
+                yield current
+            current = {}
+        if current is not None and value:
+            current[key] = scalar(value)
+    if current:
+        yield current
+
+
+def companies(text):
+    return sorted((int(e["value"], 16), e["name"]) for e in entries(text) if "value" in e)
+
+
+def appearances(text):
+    categories, subcategories = [], []
+    category = None
+    for entry in entries(text):
+        if "category" in entry:
+            category = int(entry["category"], 16)
+            categories.append((category, entry["name"]))
+        elif "value" in entry and category is not None:
+            subcategories.append(((category << 6) | int(entry["value"], 16), entry["name"]))
+    return sorted(categories), sorted(subcategories)
+
+
+def nordic_companies(text):
+    """Nordic bluetooth-numbers-database `company_ids.json`, derived from the SIG list."""
+    return sorted((e["code"], e["name"]) for e in json.loads(text))
+
+
+BTUUID_DOC = re.compile(r"^\s*/// `0x([0-9a-fA-F]{4})` (.+?)\s*$", re.MULTILINE)
+
+
+def btuuid_appearances(text):
+    """btuuid's generated `appearance/categories.rs`: `Generic <category>` for
+    sub-category 0, `<category> | <sub-category>` for the others."""
+    categories, subcategories = [], []
+    for value, label in BTUUID_DOC.findall(text):
+        value = int(value, 16)
+        if " | " in label:
+            subcategories.append((value, label.split(" | ", 1)[1]))
+        else:
+            categories.append((value >> 6, label.removeprefix("Generic ")))
+    return sorted(categories), sorted(subcategories)
+
+
+def btuuid_revision(text):
+    match = re.search(r"Commit hash: (\w+)", text)
+    return f"commit {match.group(1)}" if match else "unknown commit"
+
+
+def rust_table(name, title, note, rows, width, source):
+    escape = lambda s: s.replace("\\", "\\\\").replace('"', '\\"')
+    lines = [
+        f"// Source: {source}",
+        f"/// 📖 **{title}** ({note})",
+        f"const {name}: &[(u16, &str)] = &[",
+    ]
+    for value, label in rows:
+        number = f"0x{value:04X}" if width == 4 else str(value)
+        lines.append(f'    ({number}, "{escape(label)}"),')
+    lines.append("];")
+    return "\n".join(lines)
+
+
+def replace_block(source, marker, table):
+    pattern = re.compile(
+        rf"(// generated: {marker}\n).*?(// end generated: {marker}\n)", re.DOTALL
+    )
+    if not pattern.search(source):
+        sys.exit(f"marker {marker!r} not found in {TARGET}")
+    return pattern.sub(lambda m: f"{m.group(1)}{table}\n{m.group(2)}", source)
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("source", nargs="?", default=SIG_REPO, help="SIG public repo")
+    parser.add_argument("--companies-json", help="Nordic v1/company_ids.json")
+    parser.add_argument("--companies-revision", help="revision of --companies-json")
+    parser.add_argument("--appearance-rs", help="btuuid src/appearance/categories.rs")
+    args = parser.parse_args()
+
+    if args.companies_json:
+        text = read(args.companies_json)
+        company_rows = nordic_companies(text)
+        revision = args.companies_revision or "unknown revision"
+        company_source = (
+            "NordicSemiconductor/bluetooth-numbers-database v1/company_ids.json, "
+            f"{revision}, sha256 {sha256(text)}"
+        )
+    else:
+        text = fetch(args.source, COMPANIES_YAML)
+        company_rows = companies(text)
+        company_source = (
+            f"bluetooth-SIG/public {COMPANIES_YAML}, {sig_revision(args.source)}, "
+            f"sha256 {sha256(text)}"
+        )
+
+    if args.appearance_rs:
+        text = read(args.appearance_rs)
+        categories, subcategories = btuuid_appearances(text)
+        appearance_source = (
+            f"bluetooth-SIG/public {APPEARANCE_YAML}, {btuuid_revision(text)}, "
+            f"via btuuid appearance/categories.rs, sha256 {sha256(text)}"
+        )
+    else:
+        text = fetch(args.source, APPEARANCE_YAML)
+        categories, subcategories = appearances(text)
+        appearance_source = (
+            f"bluetooth-SIG/public {APPEARANCE_YAML}, {sig_revision(args.source)}, "
+            f"sha256 {sha256(text)}"
+        )
+
+    tables = {
+        "companies": rust_table(
+            "COMPANIES",
+            "Company identifiers",
+            "sorted by identifier",
+            company_rows,
+            4,
+            company_source,
+        ),
+        "appearance_categories": rust_table(
+            "APPEARANCE_CATEGORIES",
+            "Appearance categories",
+            "bits 15..6 of the appearance value",
+            categories,
+            0,
+            appearance_source,
+        ),
+        "appearance_subcategories": rust_table(
+            "APPEARANCE_SUBCATEGORIES",
+            "Appearance sub-categories",
+            "full 16-bit values",
+            subcategories,
+            4,
+            appearance_source,
+        ),
+    }
+
+    with open(TARGET, encoding="utf-8") as f:
+        rust = f.read()
+    for marker, table in tables.items():
+        rust = replace_block(rust, marker, table)
+    with open(TARGET, "w", encoding="utf-8") as f:
+        f.write(rust)
+
+    print(f"✅ {len(tables)} tables written to {TARGET}")
+
+
+if __name__ == "__main__":
+    main()
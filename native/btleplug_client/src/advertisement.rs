@@ -0,0 +1,100 @@
+use crate::atoms;
+use crate::central_manager_utils::properties_to_map;
+use crate::peripheral_utils::BinaryValue;
+
+use rustler::{Atom, Decoder, Encoder, Env, Error as RustlerError, NifResult, NifStruct, Term};
+
+use btleplug::api::{AddressType, PeripheralProperties};
+use std::collections::HashMap;
+
+/// ✅ **NifStruct for advertised peripheral properties**
+///
+/// Numbers stay numbers, missing values are `nil` and payloads are binaries.
+#[derive(NifStruct, Debug, Clone)]
+#[module = "RustlerBtleplug.Advertisement"]
+pub struct Advertisement {
+    pub address: String,
+    pub address_type: Option<Atom>,
+    pub local_name: Option<String>,
+    pub tx_power_level: Option<i16>,
+    pub rssi: Option<i16>,
+    pub manufacturer_data: HashMap<u16, BinaryValue>,
+    pub service_data: HashMap<String, BinaryValue>,
+    pub services: Vec<String>,
+    pub class: Option<u32>,
+    /// `list_adapters` index of the adapter that heard it
+    pub adapter: Option<usize>,
+}
+
+impl From<&PeripheralProperties> for Advertisement {
+    fn from(props: &PeripheralProperties) -> Self {
+        Advertisement {
+            address: props.address.to_string(),
+            address_type: props.address_type.map(|address_type| match address_type {
+                AddressType::Public => atoms::public(),
+                AddressType::Random => atoms::random(),
+            }),
+            local_name: props.local_name.clone(),
+            tx_power_level: props.tx_power_level,
+            rssi: props.rssi,
+            manufacturer_data: props
+                .manufacturer_data
+                .iter()
+                .map(|(id, data)| (*id, BinaryValue(data.clone())))
+                .collect(),
+            service_data: props
+                .service_data
+                .iter()
+                .map(|(uuid, data)| (uuid.to_string(), BinaryValue(data.clone())))
+                .collect(),
+            services: props.services.iter().map(|s| s.to_string()).collect(),
+            class: props.class,
+            adapter: None,
+        }
+    }
+}
+
+/// 🧾 **How discovered/updated events carry the peripheral properties**
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AdvertisementFormat {
+    /// `%RustlerBtleplug.Advertisement{}`
+    #[default]
+    Struct,
+    /// Legacy string-keyed map from `properties_to_map`
+    Map,
+}
+
+impl AdvertisementFormat {
+    pub fn encode<'a>(
+        self,
+        env: Env<'a>,
+        props: Option<&PeripheralProperties>,
+        adapter: usize,
+    ) -> Term<'a> {
+        match (self, props) {
+            (_, None) => rustler::types::atom::nil().encode(env),
+            (AdvertisementFormat::Struct, Some(props)) => Advertisement {
+                adapter: Some(adapter),
+                ..Advertisement::from(props)
+            }
+            .encode(env),
+            (AdvertisementFormat::Map, Some(props)) => {
+                let map = properties_to_map(env, props);
+                map.map_put("adapter", adapter).unwrap_or(map)
+            }
+        }
+    }
+}
+
+impl<'a> Decoder<'a> for AdvertisementFormat {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let format: Atom = term.decode()?;
+        if format == atoms::struct_() {
+            Ok(AdvertisementFormat::Struct)
+        } else if format == atoms::map() {
+            Ok(AdvertisementFormat::Map)
+        } else {
+            Err(RustlerError::BadArg)
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::atoms;
+use crate::central_manager_options::{get_option, parse_uuids};
+
+use log::debug;
+use regex::Regex;
+use rustler::{Decoder, Error as RustlerError, NifResult, Term};
+
+use btleplug::api::PeripheralProperties;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// 🚦 **Rust-side advertisement filter**
+///
+/// Every configured criterion has to match, the deny list always wins.
+/// An empty filter lets everything through.
+#[derive(Debug, Clone, Default)]
+pub struct AdvertisementFilter {
+    pub name_prefix: Option<String>,
+    pub name_regex: Option<Regex>,
+    pub min_rssi: Option<i16>,
+    pub manufacturer_ids: HashSet<u16>,
+    pub service_data_uuids: HashSet<Uuid>,
+    pub allow_addresses: HashSet<String>,
+    pub deny_addresses: HashSet<String>,
+}
+
+impl AdvertisementFilter {
+    pub fn is_empty(&self) -> bool {
+        self.name_prefix.is_none()
+            && self.name_regex.is_none()
+            && self.min_rssi.is_none()
+            && self.manufacturer_ids.is_empty()
+            && self.service_data_uuids.is_empty()
+            && self.allow_addresses.is_empty()
+            && self.deny_addresses.is_empty()
+    }
+
+    /// Addresses are matched against the BD address and the platform peripheral id
+    /// (CoreBluetooth hides the address behind a UUID).
+    fn address_in(addresses: &HashSet<String>, peripheral_id: &str, address: &str) -> bool {
+        addresses.contains(&peripheral_id.to_uppercase()) || addresses.contains(address)
+    }
+
+    pub fn matches(&self, peripheral_id: &str, properties: &PeripheralProperties) -> bool {
+        let address = properties.address.to_string().to_uppercase();
+
+        if Self::address_in(&self.deny_addresses, peripheral_id, &address) {
+            debug!("🚫 {} is on the deny list", peripheral_id);
+            return false;
+        }
+
+        if !self.allow_addresses.is_empty()
+            && !Self::address_in(&self.allow_addresses, peripheral_id, &address)
+        {
+            return false;
+        }
+
+        let local_name = properties.local_name.as_deref();
+
+        if let Some(prefix) = &self.name_prefix {
+            if !local_name.is_some_and(|name| name.starts_with(prefix.as_str())) {
+                return false;
+            }
+        }
+
+        if let Some(regex) = &self.name_regex {
+            if !local_name.is_some_and(|name| regex.is_match(name)) {
+                return false;
+            }
+        }
+
+        if let Some(min_rssi) = self.min_rssi {
+            if properties.rssi.is_none_or(|rssi| rssi < min_rssi) {
+                return false;
+            }
+        }
+
+        if !self.manufacturer_ids.is_empty()
+            && !properties
+                .manufacturer_data
+                .keys()
+                .any(|id| self.manufacturer_ids.contains(id))
+        {
+            return false;
+        }
+
+        if !self.service_data_uuids.is_empty()
+            && !properties
+                .service_data
+                .keys()
+                .any(|uuid| self.service_data_uuids.contains(uuid))
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+fn normalize_addresses(addresses: Vec<String>) -> HashSet<String> {
+    addresses
+        .into_iter()
+        .map(|address| address.to_uppercase())
+        .collect()
+}
+
+impl<'a> Decoder<'a> for AdvertisementFilter {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let name_regex = match get_option::<String>(term, "name_regex")? {
+            Some(pattern) => Some(Regex::new(&pattern).map_err(|e| {
+                RustlerError::Term(Box::new((atoms::invalid_regex(), e.to_string())))
+            })?),
+            None => None,
+        };
+
+        let service_data_uuids: Vec<String> =
+            get_option(term, "service_data_uuids")?.unwrap_or_default();
+
+        Ok(AdvertisementFilter {
+            name_prefix: get_option(term, "name_prefix")?,
+            name_regex,
+            min_rssi: get_option(term, "min_rssi")?,
+            manufacturer_ids: get_option::<Vec<u16>>(term, "manufacturer_ids")?
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            service_data_uuids: parse_uuids(&service_data_uuids)?.into_iter().collect(),
+            allow_addresses: normalize_addresses(
+                get_option(term, "allow_addresses")?.unwrap_or_default(),
+            ),
+            deny_addresses: normalize_addresses(
+                get_option(term, "deny_addresses")?.unwrap_or_default(),
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use btleplug::api::bleuuid::uuid_from_u16;
+    use btleplug::api::BDAddr;
+    use std::collections::HashMap;
+
+    const ID: &str = "hci0/dev_AA_BB_CC_DD_EE_FF";
+
+    fn sensor() -> PeripheralProperties {
+        PeripheralProperties {
+            address: BDAddr::from([0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]),
+            local_name: Some("Sensor-42".to_string()),
+            rssi: Some(-60),
+            manufacturer_data: HashMap::from([(0x004C, vec![2, 21])]),
+            service_data: HashMap::from([(uuid_from_u16(0xFCD2), vec![0x40])]),
+            ..PeripheralProperties::default()
+        }
+    }
+
+    fn addresses(addresses: &[&str]) -> HashSet<String> {
+        normalize_addresses(addresses.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn empty_filter_lets_everything_through() {
+        let filter = AdvertisementFilter::default();
+        assert!(filter.is_empty());
+        assert!(filter.matches(ID, &sensor()));
+        assert!(filter.matches(ID, &PeripheralProperties::default()));
+    }
+
+    #[test]
+    fn matches_name_prefix_and_regex() {
+        let prefix = |prefix: &str| AdvertisementFilter {
+            name_prefix: Some(prefix.to_string()),
+            ..AdvertisementFilter::default()
+        };
+        assert!(prefix("Sensor").matches(ID, &sensor()));
+        assert!(!prefix("sensor").matches(ID, &sensor()));
+        assert!(!prefix("Sensor").matches(ID, &PeripheralProperties::default()));
+
+        let regex = |pattern: &str| AdvertisementFilter {
+            name_regex: Some(Regex::new(pattern).unwrap()),
+            ..AdvertisementFilter::default()
+        };
+        assert!(regex(r"^Sensor-\d+$").matches(ID, &sensor()));
+        assert!(!regex("^Tag").matches(ID, &sensor()));
+    }
+
+    #[test]
+    fn min_rssi_needs_a_strong_enough_reading() {
+        let min_rssi = |min_rssi| AdvertisementFilter {
+            min_rssi: Some(min_rssi),
+            ..AdvertisementFilter::default()
+        };
+        assert!(min_rssi(-60).matches(ID, &sensor()));
+        assert!(!min_rssi(-59).matches(ID, &sensor()));
+
+        let no_rssi = PeripheralProperties {
+            rssi: None,
+            ..sensor()
+        };
+        assert!(!min_rssi(-100).matches(ID, &no_rssi));
+    }
+
+    #[test]
+    fn matches_manufacturer_ids_and_service_data() {
+        let manufacturers = |ids: &[u16]| AdvertisementFilter {
+            manufacturer_ids: ids.iter().copied().collect(),
+            ..AdvertisementFilter::default()
+        };
+        assert!(manufacturers(&[0x0059, 0x004C]).matches(ID, &sensor()));
+        assert!(!manufacturers(&[0x0059]).matches(ID, &sensor()));
+
+        let service_data = |uuid: u16| AdvertisementFilter {
+            service_data_uuids: HashSet::from([uuid_from_u16(uuid)]),
+            ..AdvertisementFilter::default()
+        };
+        assert!(service_data(0xFCD2).matches(ID, &sensor()));
+        assert!(!service_data(0xFEAA).matches(ID, &sensor()));
+    }
+
+    #[test]
+    fn allow_list_matches_address_or_peripheral_id() {
+        let allow = |allowed: &[&str]| AdvertisementFilter {
+            allow_addresses: addresses(allowed),
+            ..AdvertisementFilter::default()
+        };
+        assert!(allow(&["aa:bb:cc:dd:ee:ff"]).matches(ID, &sensor()));
+        assert!(allow(&[&ID.to_lowercase()]).matches(ID, &PeripheralProperties::default()));
+        assert!(!allow(&["11:22:33:44:55:66"]).matches(ID, &sensor()));
+    }
+
+    #[test]
+    fn deny_list_wins() {
+        let deny = AdvertisementFilter {
+            deny_addresses: addresses(&["AA:BB:CC:DD:EE:FF"]),
+            ..AdvertisementFilter::default()
+        };
+        assert!(!deny.matches(ID, &sensor()));
+
+        let both = AdvertisementFilter {
+            allow_addresses: addresses(&["AA:BB:CC:DD:EE:FF"]),
+            ..deny
+        };
+        assert!(!both.matches(ID, &sensor()));
+    }
+
+    #[test]
+    fn every_criterion_has_to_match() {
+        let filter = AdvertisementFilter {
+            name_prefix: Some("Sensor".to_string()),
+            min_rssi: Some(-70),
+            manufacturer_ids: HashSet::from([0x004C]),
+            ..AdvertisementFilter::default()
+        };
+        assert!(filter.matches(ID, &sensor()));
+
+        let stricter = AdvertisementFilter {
+            min_rssi: Some(-50),
+            ..filter
+        };
+        assert!(!stricter.matches(ID, &sensor()));
+    }
+}
@@ -0,0 +1,226 @@
+use crate::central_manager_options::get_option;
+
+use log::debug;
+use rustler::{Decoder, NifMap, NifResult, Term};
+
+use btleplug::api::PeripheralProperties;
+use std::collections::HashMap;
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// ⏳ **Throttling settings for `btleplug_peripheral_updated`**
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThrottleConfig {
+    /// Minimum time between two updates of the same device with unchanged data
+    pub min_interval_ms: u64,
+    /// RSSI change (dBm) that lets an update with unchanged data through early, 0 never does
+    pub rssi_delta: u16,
+}
+
+impl<'a> Decoder<'a> for ThrottleConfig {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(ThrottleConfig {
+            min_interval_ms: get_option(term, "min_interval_ms")?.unwrap_or_default(),
+            rssi_delta: get_option(term, "rssi_delta")?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Last properties reported to Elixir for a device
+#[derive(Debug)]
+struct DeviceSnapshot {
+    sent_at: Instant,
+    rssi: Option<i16>,
+    local_name: Option<String>,
+    manufacturer_data: HashMap<u16, Vec<u8>>,
+    service_data: HashMap<Uuid, Vec<u8>>,
+}
+
+impl DeviceSnapshot {
+    fn new(properties: &PeripheralProperties) -> Self {
+        DeviceSnapshot {
+            sent_at: Instant::now(),
+            rssi: properties.rssi,
+            local_name: properties.local_name.clone(),
+            manufacturer_data: properties.manufacturer_data.clone(),
+            service_data: properties.service_data.clone(),
+        }
+    }
+
+    fn payload_changed(&self, properties: &PeripheralProperties) -> bool {
+        self.local_name != properties.local_name
+            || self.manufacturer_data != properties.manufacturer_data
+            || self.service_data != properties.service_data
+    }
+}
+
+/// 📊 **Suppressed update counters**, returned by `update_throttle_stats`
+#[derive(Debug, Clone, Default, NifMap)]
+pub struct ThrottleStats {
+    pub total_suppressed: u64,
+    pub suppressed: HashMap<String, u64>,
+}
+
+/// 🚰 **Per-device throttle for `DeviceUpdated` floods**
+///
+/// Updates with a changed name, manufacturer or service data always pass.
+/// Otherwise one passes once `min_interval_ms` is over since the last update
+/// sent for the device, or earlier when the RSSI moved by `rssi_delta`.
+#[derive(Debug, Default)]
+pub struct UpdateThrottle {
+    config: Option<ThrottleConfig>,
+    devices: HashMap<String, DeviceSnapshot>,
+    stats: ThrottleStats,
+}
+
+impl UpdateThrottle {
+    pub fn new(config: Option<ThrottleConfig>) -> Self {
+        UpdateThrottle {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Replace the settings, `None` turns throttling off. Counters are kept.
+    pub fn set_config(&mut self, config: Option<ThrottleConfig>) {
+        self.config = config;
+        self.devices.clear();
+    }
+
+    pub fn stats(&self) -> ThrottleStats {
+        self.stats.clone()
+    }
+
+    /// Remember what was reported for a device (e.g. on discovery)
+    pub fn record(&mut self, peripheral_id: &str, properties: &PeripheralProperties) {
+        if self.config.is_some() {
+            self.devices
+                .insert(peripheral_id.to_string(), DeviceSnapshot::new(properties));
+        }
+    }
+
+    /// Decide whether an update is sent, recording it when it is
+    pub fn should_emit(&mut self, peripheral_id: &str, properties: &PeripheralProperties) -> bool {
+        let Some(config) = &self.config else {
+            return true;
+        };
+
+        let emit = match self.devices.get(peripheral_id) {
+            None => true,
+            Some(last) if last.payload_changed(properties) => true,
+            Some(last) => {
+                let interval_over =
+                    last.sent_at.elapsed() >= Duration::from_millis(config.min_interval_ms);
+                let rssi_moved = config.rssi_delta > 0
+                    && match (last.rssi, properties.rssi) {
+                        (Some(previous), Some(current)) => {
+                            previous.abs_diff(current) >= config.rssi_delta
+                        }
+                        (previous, current) => previous != current,
+                    };
+                interval_over || rssi_moved
+            }
+        };
+
+        if emit {
+            self.record(peripheral_id, properties);
+        } else {
+            debug!("🚰 Suppressed update for {}", peripheral_id);
+            self.stats.total_suppressed += 1;
+            *self
+                .stats
+                .suppressed
+                .entry(peripheral_id.to_string())
+                .or_default() += 1;
+        }
+
+        emit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "hci0/dev_AA_BB_CC_DD_EE_FF";
+
+    fn throttle() -> UpdateThrottle {
+        UpdateThrottle::new(Some(ThrottleConfig {
+            min_interval_ms: 1000,
+            rssi_delta: 5,
+        }))
+    }
+
+    fn beacon(rssi: Option<i16>) -> PeripheralProperties {
+        PeripheralProperties {
+            rssi,
+            manufacturer_data: HashMap::from([(0x004C, vec![2, 21])]),
+            ..PeripheralProperties::default()
+        }
+    }
+
+    /// Pretend the last update of the device went out `ms` ago
+    fn age(throttle: &mut UpdateThrottle, ms: u64) {
+        throttle.devices.get_mut(ID).unwrap().sent_at = Instant::now() - Duration::from_millis(ms);
+    }
+
+    #[test]
+    fn without_config_everything_passes() {
+        let mut throttle = UpdateThrottle::new(None);
+        assert!(throttle.should_emit(ID, &beacon(Some(-60))));
+        assert!(throttle.should_emit(ID, &beacon(Some(-60))));
+        assert_eq!(throttle.stats().total_suppressed, 0);
+    }
+
+    #[test]
+    fn steady_rssi_passes_once_the_interval_is_over() {
+        let mut throttle = throttle();
+        assert!(throttle.should_emit(ID, &beacon(Some(-60))));
+        assert!(!throttle.should_emit(ID, &beacon(Some(-61))));
+
+        age(&mut throttle, 1000);
+        assert!(throttle.should_emit(ID, &beacon(Some(-60))));
+        assert!(!throttle.should_emit(ID, &beacon(Some(-60))));
+        assert_eq!(throttle.stats().suppressed[ID], 2);
+    }
+
+    #[test]
+    fn missing_rssi_passes_once_the_interval_is_over() {
+        let mut throttle = throttle();
+        assert!(throttle.should_emit(ID, &beacon(None)));
+        assert!(!throttle.should_emit(ID, &beacon(None)));
+
+        age(&mut throttle, 1500);
+        assert!(throttle.should_emit(ID, &beacon(None)));
+    }
+
+    #[test]
+    fn rssi_jump_passes_within_the_interval() {
+        let mut throttle = throttle();
+        assert!(throttle.should_emit(ID, &beacon(Some(-60))));
+        assert!(!throttle.should_emit(ID, &beacon(Some(-64))));
+        assert!(throttle.should_emit(ID, &beacon(Some(-70))));
+        assert!(throttle.should_emit(ID, &beacon(None)));
+    }
+
+    #[test]
+    fn zero_rssi_delta_only_uses_the_interval() {
+        let mut throttle = UpdateThrottle::new(Some(ThrottleConfig {
+            min_interval_ms: 1000,
+            rssi_delta: 0,
+        }));
+        assert!(throttle.should_emit(ID, &beacon(Some(-60))));
+        assert!(!throttle.should_emit(ID, &beacon(Some(-90))));
+    }
+
+    #[test]
+    fn changed_payload_always_passes() {
+        let mut throttle = throttle();
+        assert!(throttle.should_emit(ID, &beacon(Some(-60))));
+
+        let mut changed = beacon(Some(-60));
+        changed.manufacturer_data.insert(0x004C, vec![2, 22]);
+        assert!(throttle.should_emit(ID, &changed));
+        assert_eq!(throttle.stats().total_suppressed, 0);
+    }
+}
@@ -9,12 +9,167 @@ rustler::atoms! {
 
     candidate_error,
 
+    timeout,
+    service_discovery_failed,
+    service_not_found,
+    peripheral_not_found,
+    characteristic_not_found,
+    descriptor_not_found,
+    ambiguous_characteristic,
+    not_readable,
+    not_writable,
+    notify_not_supported,
+    connection_failed,
+    invalid_write_type,
+    invalid_uuid,
+    invalid_regex,
+
+    // service discovery policies
+    never,
+    always,
+    allow_listed,
+
+    // scan stop reasons
+    stopped,
+    replaced,
+
+    // advertisement formats
+    struct_ = "struct",
+    map,
+
+    // address types
+    public,
+    random,
+
+    // beacon fields
+    type_ = "type",
+    ibeacon,
+    altbeacon,
+    eddystone_uid,
+    eddystone_url,
+    eddystone_tlm,
+    eddystone_eid,
+    uuid,
+    major,
+    minor,
+    measured_power,
+    company_id,
+    manufacturer_reserved,
+    tx_power,
+    namespace,
+    instance,
+    url,
+    battery_mv,
+    temperature,
+    adv_count,
+    uptime_ms,
+    eid,
+    rssi,
+
+    // BTHome
+    bthome,
+    format,
+    encrypted,
+    trigger_based,
+    packet_id,
+    measurements,
+    name,
+    value,
+    unit,
+    unsupported_version,
+    missing_bindkey,
+    decryption_failed,
+    truncated,
+    invalid_bindkey,
+    invalid_address,
+
+    // assigned numbers
+    service,
+    characteristic,
+    descriptor,
+    company,
+    appearance,
+
+    // GATT values
+    battery_level,
+    heart_rate_measurement,
+    heart_rate,
+    sensor_contact,
+    energy_expended_kj,
+    rr_intervals_ms,
+    temperature_measurement,
+    timestamp,
+    temperature_type,
+    armpit,
+    body,
+    ear,
+    finger,
+    gastrointestinal,
+    mouth,
+    rectum,
+    toe,
+    tympanum,
+    csc_measurement,
+    cumulative_wheel_revolutions,
+    last_wheel_event_time,
+    cumulative_crank_revolutions,
+    last_crank_event_time,
+    rsc_measurement,
+    speed_mps,
+    cadence,
+    stride_length_m,
+    total_distance_m,
+    running,
+    weight_measurement,
+    weight,
+    user_id,
+    bmi,
+    height,
+    height_unit,
+    blood_pressure_measurement,
+    systolic,
+    diastolic,
+    mean_arterial_pressure,
+    pulse_rate,
+    measurement_status,
+
+    // payload schemas
+    bitfield,
+    string,
+    bytes,
+    scale,
+    invalid_schema,
+    missing_field,
+    invalid_field,
+    no_payload_schema,
+
+    // adapters
+    adapter_not_found,
+    all,
+    powered_on,
+    powered_off,
+    unknown,
+
+    // connect by address
+    scanning,
+    found,
+    connecting,
+
+    // write types
+    with_response,
+    without_response,
+
     btleplug_error,
     btleplug_got_central,
     btleplug_no_adapters_found,
     btleplug_adapter_status_update,
+    btleplug_adapter_lost,
+    btleplug_adapter_recovered,
     btleplug_scan_started,
     btleplug_scan_stopped,
+    btleplug_scan_report,
+    btleplug_beacon,
+    btleplug_sensor_reading,
 
     btleplug_peripheral_discovered,
     btleplug_peripheral_discovery_error,
@@ -30,4 +185,7 @@ rustler::atoms! {
     btleplug_services_advertisement,
 
     btleplug_characteristic_value_changed,
+
+    btleplug_op_result,
+    btleplug_op_progress,
 }
@@ -0,0 +1,246 @@
+use crate::atoms;
+use crate::central_manager_options::parse_uuid;
+use crate::peripheral_utils::atom_map;
+
+use rustler::{Atom, Binary, Encoder, Env, NifResult, Term};
+
+use btleplug::api::bleuuid::uuid_from_u16;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const APPLE_COMPANY_ID: u16 = 0x004C;
+const EDDYSTONE_SERVICE_UUID: u16 = 0xFEAA;
+
+const EDDYSTONE_URL_SCHEMES: [&str; 4] = ["http://www.", "https://www.", "http://", "https://"];
+const EDDYSTONE_URL_EXPANSIONS: [&str; 14] = [
+    ".com/", ".org/", ".edu/", ".net/", ".info/", ".biz/", ".gov/", ".com", ".org", ".edu", ".net",
+    ".info", ".biz", ".gov",
+];
+
+/// 📍 **Decoded beacon frame**
+#[derive(Debug, Clone, PartialEq)]
+pub enum BeaconFrame {
+    IBeacon {
+        uuid: Uuid,
+        major: u16,
+        minor: u16,
+        measured_power: i8,
+    },
+    AltBeacon {
+        company_id: u16,
+        uuid: Uuid,
+        major: u16,
+        minor: u16,
+        measured_power: i8,
+        manufacturer_reserved: u8,
+    },
+    EddystoneUid {
+        tx_power: i8,
+        namespace: String,
+        instance: String,
+    },
+    EddystoneUrl {
+        tx_power: i8,
+        url: String,
+    },
+    EddystoneTlm {
+        /// `None` when the beacon is externally powered
+        battery_mv: Option<u16>,
+        /// °C, `None` when not supported
+        temperature: Option<f64>,
+        adv_count: u32,
+        uptime_ms: u64,
+    },
+    EddystoneEid {
+        tx_power: i8,
+        eid: String,
+    },
+}
+
+impl BeaconFrame {
+    /// 🍏 **iBeacon** (Apple) or **AltBeacon** (any company) in manufacturer data
+    pub fn from_manufacturer_data(company_id: u16, data: &[u8]) -> Option<BeaconFrame> {
+        match data {
+            [0x02, 0x15, rest @ ..] if company_id == APPLE_COMPANY_ID && rest.len() == 21 => {
+                Some(BeaconFrame::IBeacon {
+                    uuid: Uuid::from_slice(&rest[0..16]).ok()?,
+                    major: u16::from_be_bytes([rest[16], rest[17]]),
+                    minor: u16::from_be_bytes([rest[18], rest[19]]),
+                    measured_power: rest[20] as i8,
+                })
+            }
+            [0xBE, 0xAC, rest @ ..] if rest.len() == 22 => Some(BeaconFrame::AltBeacon {
+                company_id,
+                uuid: Uuid::from_slice(&rest[0..16]).ok()?,
+                major: u16::from_be_bytes([rest[16], rest[17]]),
+                minor: u16::from_be_bytes([rest[18], rest[19]]),
+                measured_power: rest[20] as i8,
+                manufacturer_reserved: rest[21],
+            }),
+            _ => None,
+        }
+    }
+
+    /// 🔭 **Eddystone** frames in `0xFEAA` service data
+    pub fn from_service_data(service_uuid: &Uuid, data: &[u8]) -> Option<BeaconFrame> {
+        if *service_uuid != uuid_from_u16(EDDYSTONE_SERVICE_UUID) {
+            return None;
+        }
+
+        match data {
+            [0x00, tx_power, rest @ ..] if rest.len() >= 16 => Some(BeaconFrame::EddystoneUid {
+                tx_power: *tx_power as i8,
+                namespace: to_hex(&rest[0..10]),
+                instance: to_hex(&rest[10..16]),
+            }),
+            [0x10, tx_power, scheme, encoded @ ..] => Some(BeaconFrame::EddystoneUrl {
+                tx_power: *tx_power as i8,
+                url: decode_eddystone_url(*scheme, encoded)?,
+            }),
+            // Only the unencrypted TLM version is readable
+            [0x20, 0x00, rest @ ..] if rest.len() >= 12 => {
+                let battery_mv = u16::from_be_bytes([rest[0], rest[1]]);
+                let temperature = i16::from_be_bytes([rest[2], rest[3]]);
+                let sec_count = u32::from_be_bytes([rest[8], rest[9], rest[10], rest[11]]);
+                Some(BeaconFrame::EddystoneTlm {
+                    battery_mv: (battery_mv != 0).then_some(battery_mv),
+                    // 8.8 fixed point, 0x8000 means not supported
+                    temperature: (temperature != i16::MIN).then_some(temperature as f64 / 256.0),
+                    adv_count: u32::from_be_bytes([rest[4], rest[5], rest[6], rest[7]]),
+                    uptime_ms: sec_count as u64 * 100,
+                })
+            }
+            [0x30, tx_power, rest @ ..] if rest.len() >= 8 => Some(BeaconFrame::EddystoneEid {
+                tx_power: *tx_power as i8,
+                eid: to_hex(&rest[0..8]),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn from_advertised_manufacturer_data(
+        manufacturer_data: &HashMap<u16, Vec<u8>>,
+    ) -> Vec<BeaconFrame> {
+        manufacturer_data
+            .iter()
+            .filter_map(|(company_id, data)| BeaconFrame::from_manufacturer_data(*company_id, data))
+            .collect()
+    }
+
+    pub fn from_advertised_service_data(service_data: &HashMap<Uuid, Vec<u8>>) -> Vec<BeaconFrame> {
+        service_data
+            .iter()
+            .filter_map(|(uuid, data)| BeaconFrame::from_service_data(uuid, data))
+            .collect()
+    }
+
+    fn fields<'a>(&self, env: Env<'a>) -> Vec<(Atom, Term<'a>)> {
+        match self {
+            BeaconFrame::IBeacon {
+                uuid,
+                major,
+                minor,
+                measured_power,
+            } => vec![
+                (atoms::type_(), atoms::ibeacon().encode(env)),
+                (atoms::uuid(), uuid.to_string().encode(env)),
+                (atoms::major(), major.encode(env)),
+                (atoms::minor(), minor.encode(env)),
+                (atoms::measured_power(), measured_power.encode(env)),
+            ],
+            BeaconFrame::AltBeacon {
+                company_id,
+                uuid,
+                major,
+                minor,
+                measured_power,
+                manufacturer_reserved,
+            } => vec![
+                (atoms::type_(), atoms::altbeacon().encode(env)),
+                (atoms::company_id(), company_id.encode(env)),
+                (atoms::uuid(), uuid.to_string().encode(env)),
+                (atoms::major(), major.encode(env)),
+                (atoms::minor(), minor.encode(env)),
+                (atoms::measured_power(), measured_power.encode(env)),
+                (
+                    atoms::manufacturer_reserved(),
+                    manufacturer_reserved.encode(env),
+                ),
+            ],
+            BeaconFrame::EddystoneUid {
+                tx_power,
+                namespace,
+                instance,
+            } => vec![
+                (atoms::type_(), atoms::eddystone_uid().encode(env)),
+                (atoms::tx_power(), tx_power.encode(env)),
+                (atoms::namespace(), namespace.encode(env)),
+                (atoms::instance(), instance.encode(env)),
+            ],
+            BeaconFrame::EddystoneUrl { tx_power, url } => vec![
+                (atoms::type_(), atoms::eddystone_url().encode(env)),
+                (atoms::tx_power(), tx_power.encode(env)),
+                (atoms::url(), url.encode(env)),
+            ],
+            BeaconFrame::EddystoneTlm {
+                battery_mv,
+                temperature,
+                adv_count,
+                uptime_ms,
+            } => vec![
+                (atoms::type_(), atoms::eddystone_tlm().encode(env)),
+                (atoms::battery_mv(), battery_mv.encode(env)),
+                (atoms::temperature(), temperature.encode(env)),
+                (atoms::adv_count(), adv_count.encode(env)),
+                (atoms::uptime_ms(), uptime_ms.encode(env)),
+            ],
+            BeaconFrame::EddystoneEid { tx_power, eid } => vec![
+                (atoms::type_(), atoms::eddystone_eid().encode(env)),
+                (atoms::tx_power(), tx_power.encode(env)),
+                (atoms::eid(), eid.encode(env)),
+            ],
+        }
+    }
+}
+
+/// Encodes as `%{type: :ibeacon | :altbeacon | :eddystone_uid | ..., ...fields}`
+impl Encoder for BeaconFrame {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        atom_map(env, self.fields(env))
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_eddystone_url(scheme: u8, encoded: &[u8]) -> Option<String> {
+    let mut url = EDDYSTONE_URL_SCHEMES.get(scheme as usize)?.to_string();
+
+    for byte in encoded {
+        match EDDYSTONE_URL_EXPANSIONS.get(*byte as usize) {
+            Some(expansion) => url.push_str(expansion),
+            None if (0x21..0x7F).contains(byte) => url.push(*byte as char),
+            None => return None,
+        }
+    }
+
+    Some(url)
+}
+
+/// 🔍 **Decode a single payload**: company id + manufacturer data or service UUID + service data
+#[rustler::nif]
+pub fn decode_beacon(source: Term, data: Binary) -> NifResult<Option<BeaconFrame>> {
+    if let Ok(company_id) = source.decode::<u16>() {
+        return Ok(BeaconFrame::from_manufacturer_data(
+            company_id,
+            data.as_slice(),
+        ));
+    }
+
+    let service_uuid = parse_uuid(&source.decode::<String>()?)?;
+    Ok(BeaconFrame::from_service_data(
+        &service_uuid,
+        data.as_slice(),
+    ))
+}
@@ -0,0 +1,429 @@
+use crate::atoms;
+use crate::peripheral_utils::{atom_map, BinaryValue};
+
+use aes::Aes128;
+use ccm::aead::{Aead, KeyInit};
+use ccm::consts::{U13, U4};
+use ccm::Ccm;
+use log::debug;
+use rustler::{Atom, Binary, Decoder, Encoder, Env, Error as RustlerError, NifResult, Term};
+
+use btleplug::api::bleuuid::uuid_from_u16;
+use btleplug::api::BDAddr;
+use uuid::Uuid;
+
+pub const BTHOME_SERVICE_UUID: u16 = 0xFCD2;
+
+type BthomeCcm = Ccm<Aes128, U4, U13>;
+
+/// How an object's payload is laid out
+#[derive(Debug, Clone, Copy)]
+enum ObjectKind {
+    Unsigned(usize, f64),
+    Signed(usize, f64),
+    Binary,
+    Button,
+    Dimmer,
+    Text,
+    Raw,
+}
+
+use ObjectKind::*;
+
+/// BTHome v2 object ids: (id, name, layout, unit)
+const OBJECTS: &[(u8, &str, ObjectKind, Option<&str>)] = &[
+    (0x00, "packet_id", Unsigned(1, 1.0), None),
+    (0x01, "battery", Unsigned(1, 1.0), Some("%")),
+    (0x02, "temperature", Signed(2, 0.01), Some("°C")),
+    (0x03, "humidity", Unsigned(2, 0.01), Some("%")),
+    (0x04, "pressure", Unsigned(3, 0.01), Some("hPa")),
+    (0x05, "illuminance", Unsigned(3, 0.01), Some("lx")),
+    (0x06, "mass", Unsigned(2, 0.01), Some("kg")),
+    (0x07, "mass", Unsigned(2, 0.01), Some("lb")),
+    (0x08, "dewpoint", Signed(2, 0.01), Some("°C")),
+    (0x09, "count", Unsigned(1, 1.0), None),
+    (0x0A, "energy", Unsigned(3, 0.001), Some("kWh")),
+    (0x0B, "power", Unsigned(3, 0.01), Some("W")),
+    (0x0C, "voltage", Unsigned(2, 0.001), Some("V")),
+    (0x0D, "pm2_5", Unsigned(2, 1.0), Some("µg/m³")),
+    (0x0E, "pm10", Unsigned(2, 1.0), Some("µg/m³")),
+    (0x0F, "generic_boolean", Binary, None),
+    (0x10, "power_on", Binary, None),
+    (0x11, "opening", Binary, None),
+    (0x12, "co2", Unsigned(2, 1.0), Some("ppm")),
+    (0x13, "tvoc", Unsigned(2, 1.0), Some("µg/m³")),
+    (0x14, "moisture", Unsigned(2, 0.01), Some("%")),
+    (0x15, "battery_low", Binary, None),
+    (0x16, "battery_charging", Binary, None),
+    (0x17, "carbon_monoxide", Binary, None),
+    (0x18, "cold", Binary, None),
+    (0x19, "connectivity", Binary, None),
+    (0x1A, "door", Binary, None),
+    (0x1B, "garage_door", Binary, None),
+    (0x1C, "gas_detected", Binary, None),
+    (0x1D, "heat", Binary, None),
+    (0x1E, "light", Binary, None),
+    (0x1F, "lock", Binary, None),
+    (0x20, "moisture_detected", Binary, None),
+    (0x21, "motion", Binary, None),
+    (0x22, "moving", Binary, None),
+    (0x23, "occupancy", Binary, None),
+    (0x24, "plug", Binary, None),
+    (0x25, "presence", Binary, None),
+    (0x26, "problem", Binary, None),
+    (0x27, "running", Binary, None),
+    (0x28, "safety", Binary, None),
+    (0x29, "smoke", Binary, None),
+    (0x2A, "sound", Binary, None),
+    (0x2B, "tamper", Binary, None),
+    (0x2C, "vibration", Binary, None),
+    (0x2D, "window", Binary, None),
+    (0x2E, "humidity", Unsigned(1, 1.0), Some("%")),
+    (0x2F, "moisture", Unsigned(1, 1.0), Some("%")),
+    (0x3A, "button", Button, None),
+    (0x3C, "dimmer", Dimmer, None),
+    (0x3D, "count", Unsigned(2, 1.0), None),
+    (0x3E, "count", Unsigned(4, 1.0), None),
+    (0x3F, "rotation", Signed(2, 0.1), Some("°")),
+    (0x40, "distance", Unsigned(2, 1.0), Some("mm")),
+    (0x41, "distance", Unsigned(2, 0.1), Some("m")),
+    (0x42, "duration", Unsigned(3, 0.001), Some("s")),
+    (0x43, "current", Unsigned(2, 0.001), Some("A")),
+    (0x44, "speed", Unsigned(2, 0.01), Some("m/s")),
+    (0x45, "temperature", Signed(2, 0.1), Some("°C")),
+    (0x46, "uv_index", Unsigned(1, 0.1), None),
+    (0x47, "volume", Unsigned(2, 0.1), Some("L")),
+    (0x48, "volume", Unsigned(2, 1.0), Some("mL")),
+    (0x49, "volume_flow_rate", Unsigned(2, 0.001), Some("m³/h")),
+    (0x4A, "voltage", Unsigned(2, 0.1), Some("V")),
+    (0x4B, "gas", Unsigned(3, 0.001), Some("m³")),
+    (0x4C, "gas", Unsigned(4, 0.001), Some("m³")),
+    (0x4D, "energy", Unsigned(4, 0.001), Some("kWh")),
+    (0x4E, "volume", Unsigned(4, 0.001), Some("L")),
+    (0x4F, "water", Unsigned(4, 0.001), Some("L")),
+    (0x50, "timestamp", Unsigned(4, 1.0), Some("s")),
+    (0x51, "acceleration", Unsigned(2, 0.001), Some("m/s²")),
+    (0x52, "gyroscope", Unsigned(2, 0.001), Some("°/s")),
+    (0x53, "text", Text, None),
+    (0x54, "raw", Raw, None),
+    (0x55, "volume_storage", Unsigned(4, 0.001), Some("L")),
+    (0x56, "conductivity", Unsigned(2, 1.0), Some("µS/cm")),
+    (0x57, "temperature", Signed(1, 1.0), Some("°C")),
+    (0x58, "temperature", Signed(1, 0.35), Some("°C")),
+    (0x59, "count", Signed(1, 1.0), None),
+    (0x5A, "count", Signed(2, 1.0), None),
+    (0x5B, "count", Signed(4, 1.0), None),
+    (0x5C, "power", Signed(4, 0.01), Some("W")),
+    (0x5D, "current", Signed(2, 0.001), Some("A")),
+    (0x5E, "direction", Unsigned(2, 0.01), Some("°")),
+    (0x5F, "precipitation", Unsigned(2, 0.1), Some("mm")),
+    (0x60, "channel", Unsigned(1, 1.0), None),
+    (0x61, "rotational_speed", Unsigned(2, 1.0), Some("rpm")),
+    (0xF0, "device_type_id", Unsigned(2, 1.0), None),
+    (0xF1, "firmware_version", Unsigned(4, 1.0), None),
+    (0xF2, "firmware_version", Unsigned(3, 1.0), None),
+];
+
+const BUTTON_EVENTS: [&str; 7] = [
+    "none",
+    "press",
+    "double_press",
+    "triple_press",
+    "long_press",
+    "long_double_press",
+    "long_triple_press",
+];
+
+/// 🌡️ **Measured value**, scaled by the object's factor
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeasurementValue {
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Event(&'static str),
+    Dimmer { event: &'static str, steps: u8 },
+    Text(String),
+    Raw(Vec<u8>),
+}
+
+impl Encoder for MeasurementValue {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            MeasurementValue::Integer(value) => value.encode(env),
+            MeasurementValue::Float(value) => value.encode(env),
+            MeasurementValue::Bool(value) => value.encode(env),
+            MeasurementValue::Event(event) => encode_name(env, event),
+            MeasurementValue::Dimmer { event, steps } => {
+                (encode_name(env, event), steps).encode(env)
+            }
+            MeasurementValue::Text(text) => text.encode(env),
+            MeasurementValue::Raw(data) => BinaryValue(data.clone()).encode(env),
+        }
+    }
+}
+
+/// Names come from the static object table, so the atom table stays bounded
+fn encode_name<'a>(env: Env<'a>, name: &str) -> Term<'a> {
+    Atom::from_str(env, name)
+        .map(|atom| atom.encode(env))
+        .unwrap_or_else(|_| name.encode(env))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub name: &'static str,
+    pub value: MeasurementValue,
+    pub unit: Option<&'static str>,
+}
+
+impl Encoder for Measurement {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        atom_map(
+            env,
+            vec![
+                (atoms::name(), encode_name(env, self.name)),
+                (atoms::value(), self.value.encode(env)),
+                (atoms::unit(), self.unit.encode(env)),
+            ],
+        )
+    }
+}
+
+/// 📡 **Payload of `btleplug_sensor_reading`**
+#[derive(Debug, Clone)]
+pub struct SensorReading {
+    pub encrypted: bool,
+    pub trigger_based: bool,
+    pub packet_id: Option<u8>,
+    pub measurements: Vec<Measurement>,
+}
+
+impl Encoder for SensorReading {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        atom_map(
+            env,
+            vec![
+                (atoms::format(), atoms::bthome().encode(env)),
+                (atoms::encrypted(), self.encrypted.encode(env)),
+                (atoms::trigger_based(), self.trigger_based.encode(env)),
+                (atoms::packet_id(), self.packet_id.encode(env)),
+                (atoms::measurements(), self.measurements.encode(env)),
+            ],
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BthomeError {
+    UnsupportedVersion(u8),
+    MissingBindkey,
+    DecryptionFailed,
+    Truncated,
+}
+
+impl Encoder for BthomeError {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            BthomeError::UnsupportedVersion(version) => {
+                (atoms::unsupported_version(), version).encode(env)
+            }
+            BthomeError::MissingBindkey => atoms::missing_bindkey().encode(env),
+            BthomeError::DecryptionFailed => atoms::decryption_failed().encode(env),
+            BthomeError::Truncated => atoms::truncated().encode(env),
+        }
+    }
+}
+
+/// 🔑 **AES-128 bindkey**, given as 32 hex characters or 16 raw bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bindkey(pub [u8; 16]);
+
+impl<'a> Decoder<'a> for Bindkey {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let binary: Binary = term.decode()?;
+        let invalid = || RustlerError::Term(Box::new(atoms::invalid_bindkey()));
+
+        let bytes = match binary.len() {
+            16 => binary.as_slice().to_vec(),
+            // Byte pairs, so multibyte UTF-8 is rejected instead of sliced mid-character
+            32 => binary
+                .as_slice()
+                .chunks(2)
+                .map(|pair| {
+                    let high = char::from(pair[0]).to_digit(16)?;
+                    let low = char::from(pair[1]).to_digit(16)?;
+                    Some((high * 16 + low) as u8)
+                })
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        };
+
+        Ok(Bindkey(bytes.try_into().map_err(|_| invalid())?))
+    }
+}
+
+pub fn is_bthome_uuid(service_uuid: &Uuid) -> bool {
+    *service_uuid == uuid_from_u16(BTHOME_SERVICE_UUID)
+}
+
+/// 🏠 **Decode BTHome v2 service data**, decrypting it with `bindkey` if needed
+pub fn decode_bthome_payload(
+    data: &[u8],
+    address: Option<BDAddr>,
+    bindkey: Option<&Bindkey>,
+) -> Result<SensorReading, BthomeError> {
+    let (&device_info, payload) = data.split_first().ok_or(BthomeError::Truncated)?;
+
+    let version = device_info >> 5;
+    if version != 2 {
+        return Err(BthomeError::UnsupportedVersion(version));
+    }
+
+    let encrypted = device_info & 0x01 != 0;
+    let plaintext = if encrypted {
+        let bindkey = bindkey.ok_or(BthomeError::MissingBindkey)?;
+        decrypt(device_info, payload, address, bindkey)?
+    } else {
+        payload.to_vec()
+    };
+
+    let measurements = parse_objects(&plaintext)?;
+    let packet_id = measurements.iter().find_map(|m| match (m.name, &m.value) {
+        ("packet_id", MeasurementValue::Integer(id)) => Some(*id as u8),
+        _ => None,
+    });
+
+    Ok(SensorReading {
+        encrypted,
+        trigger_based: device_info & 0x04 != 0,
+        packet_id,
+        measurements: measurements
+            .into_iter()
+            .filter(|m| m.name != "packet_id")
+            .collect(),
+    })
+}
+
+/// 🔐 AES-CCM, nonce = MAC + UUID + device info + counter, 4 byte MIC
+fn decrypt(
+    device_info: u8,
+    payload: &[u8],
+    address: Option<BDAddr>,
+    bindkey: &Bindkey,
+) -> Result<Vec<u8>, BthomeError> {
+    if payload.len() < 8 {
+        return Err(BthomeError::Truncated);
+    }
+
+    let address = address.ok_or(BthomeError::DecryptionFailed)?;
+    let (ciphertext, trailer) = payload.split_at(payload.len() - 8);
+    let (counter, mic) = trailer.split_at(4);
+
+    let mut nonce = Vec::with_capacity(13);
+    nonce.extend_from_slice(&address.into_inner());
+    nonce.extend_from_slice(&BTHOME_SERVICE_UUID.to_le_bytes());
+    nonce.push(device_info);
+    nonce.extend_from_slice(counter);
+
+    let mut sealed = ciphertext.to_vec();
+    sealed.extend_from_slice(mic);
+
+    BthomeCcm::new((&bindkey.0).into())
+        .decrypt(nonce.as_slice().into(), sealed.as_slice())
+        .map_err(|_| {
+            debug!("🔐 BTHome decryption failed for {}", address);
+            BthomeError::DecryptionFailed
+        })
+}
+
+fn read_le(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0u64, |value, byte| (value << 8) | *byte as u64)
+}
+
+fn scaled(raw: i64, factor: f64) -> MeasurementValue {
+    if factor == 1.0 {
+        MeasurementValue::Integer(raw)
+    } else {
+        // Round away float noise like 21.500000000000004
+        MeasurementValue::Float((raw as f64 * factor * 1e6).round() / 1e6)
+    }
+}
+
+fn parse_objects(mut data: &[u8]) -> Result<Vec<Measurement>, BthomeError> {
+    let mut measurements = Vec::new();
+
+    while let Some((&object_id, rest)) = data.split_first() {
+        let Some(&(_, name, kind, unit)) = OBJECTS.iter().find(|(id, ..)| *id == object_id) else {
+            // Unknown ids have unknown lengths, nothing after them can be parsed
+            debug!("⚠️ Unknown BTHome object id 0x{:02X}", object_id);
+            break;
+        };
+
+        let len = match kind {
+            Unsigned(len, _) | Signed(len, _) => len,
+            Binary | Button => 1,
+            Dimmer => 2,
+            Text | Raw => 1 + *rest.first().ok_or(BthomeError::Truncated)? as usize,
+        };
+        if rest.len() < len {
+            return Err(BthomeError::Truncated);
+        }
+        let (bytes, remaining) = rest.split_at(len);
+
+        let value = match kind {
+            Unsigned(_, factor) => scaled(read_le(bytes) as i64, factor),
+            Signed(len, factor) => {
+                let shift = 64 - 8 * len as u32;
+                scaled(((read_le(bytes) << shift) as i64) >> shift, factor)
+            }
+            Binary => MeasurementValue::Bool(bytes[0] != 0),
+            Button => MeasurementValue::Event(button_event(bytes[0])),
+            Dimmer => MeasurementValue::Dimmer {
+                event: match bytes[0] {
+                    1 => "rotate_left",
+                    2 => "rotate_right",
+                    _ => "none",
+                },
+                steps: bytes[1],
+            },
+            Text => MeasurementValue::Text(String::from_utf8_lossy(&bytes[1..]).into_owned()),
+            Raw => MeasurementValue::Raw(bytes[1..].to_vec()),
+        };
+
+        measurements.push(Measurement { name, value, unit });
+        data = remaining;
+    }
+
+    Ok(measurements)
+}
+
+fn button_event(event: u8) -> &'static str {
+    match event {
+        0x80 => "hold_press",
+        event => BUTTON_EVENTS.get(event as usize).copied().unwrap_or("none"),
+    }
+}
+
+/// 🔍 **Decode a single BTHome v2 service data payload**
+#[rustler::nif]
+pub fn decode_bthome(
+    data: Binary,
+    address: Option<String>,
+    bindkey: Option<Bindkey>,
+) -> Result<SensorReading, RustlerError> {
+    let address = match address {
+        Some(address) => Some(
+            address
+                .parse::<BDAddr>()
+                .map_err(|_| RustlerError::Term(Box::new((atoms::invalid_address(), address))))?,
+        ),
+        None => None,
+    };
+
+    decode_bthome_payload(data.as_slice(), address, bindkey.as_ref())
+        .map_err(|e| RustlerError::Term(Box::new(e)))
+}
@@ -1,30 +1,51 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
+use crate::advertisement_filter::AdvertisementFilter;
+use crate::advertisement_throttle::{ThrottleConfig, ThrottleStats};
 use crate::atoms;
-
+use crate::beacon::BeaconFrame;
+use crate::bthome::{decode_bthome_payload, is_bthome_uuid, Bindkey};
+
+use crate::central_manager_adapters::{
+    select_adapters, start_scan_all, stop_scan_all, AdapterEvent, AdapterPowerState, AdapterStates,
+};
+use crate::central_manager_options::{CentralOptions, ScanOptions};
+use crate::central_manager_recovery::spawn_adapter_events;
 use crate::central_manager_state::cache_rssi;
 use crate::central_manager_state::CentralManagerState;
 use crate::central_manager_state::CentralRef;
 use crate::central_manager_state::DISCOVERED_SERVICES;
+use crate::central_manager_state::{ScanStatus, ScanStoppedInfo};
 use crate::central_manager_utils::*;
+use crate::peripheral::{PeripheralState, PeripheralStateEnum};
+use crate::peripheral_utils::system_time_us;
+use crate::scan_report::{record_for_report, ScanReport};
 
 use log::{debug, info, warn};
-use rustler::{Encoder, Env, Error as RustlerError, LocalPid, OwnedEnv, ResourceArc};
+use rustler::{Atom, Encoder, Env, Error as RustlerError, LocalPid, OwnedEnv, ResourceArc};
 
 use btleplug::api::{Central, CentralEvent, Manager as _, Peripheral, ScanFilter};
 use btleplug::platform::{Adapter, Manager};
-use futures::StreamExt;
 
 use crate::RUNTIME;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
 use tokio::sync::mpsc;
 use tokio::sync::RwLock;
 use tokio::time::{sleep, Duration};
+use uuid::Uuid;
 
 #[rustler::nif]
-pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>, RustlerError> {
-    info!("Creating CentralManager... {:?}", pid.as_c_arg());
+pub fn create_central(
+    env: Env,
+    pid: LocalPid,
+    options: CentralOptions,
+) -> Result<ResourceArc<CentralRef>, RustlerError> {
+    info!(
+        "Creating CentralManager... {:?}, options: {:?}",
+        pid.as_c_arg(),
+        options
+    );
 
     let manager = RUNTIME
         .block_on(Manager::new())
@@ -38,64 +59,115 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
         return Err(RustlerError::Term(Box::new("No available adapter")));
     }
 
-    let adapter = adapters.into_iter().next().unwrap();
-    let adapter_clone = adapter.clone();
-    let adapter_info = RUNTIME.block_on(adapter.adapter_info());
-    info!("✅ Adapter initialized: {:?}", adapter_info);
-
-    let (event_sender, event_receiver) = mpsc::channel::<CentralEvent>(100);
+    let adapters = RUNTIME.block_on(select_adapters(
+        adapters,
+        options.adapter.as_ref(),
+        options.adapters.as_ref(),
+    ))?;
+    info!(
+        "✅ Adapters initialized: {:?}",
+        adapters.iter().map(|a| &a.info).collect::<Vec<_>>()
+    );
+
+    let (event_sender, event_receiver) = mpsc::channel::<AdapterEvent>(100);
     let event_receiver = Arc::new(RwLock::new(event_receiver));
     let event_receiver_clone = event_receiver.clone();
-    let event_sender_clone = event_sender.clone();
 
-    let state =
-        CentralManagerState::new(pid, manager, adapter.clone(), event_sender, event_receiver);
+    let state = CentralManagerState::new(
+        pid,
+        manager,
+        adapters.clone(),
+        event_sender.clone(),
+        event_receiver,
+        &options,
+    );
+    state
+        .adapter_state
+        .send_replace(RUNTIME.block_on(AdapterStates::query(&adapters)));
+    let adapter_state = state.adapter_state.clone();
+    let central_adapters = state.adapters.clone();
+    let adapter_rssi = state.adapter_rssi.clone();
+    let discovered_peripherals = state.discovered_peripherals.clone();
+    let scan_filter = state.scan_filter.clone();
+    let advertisement_filter = state.advertisement_filter.clone();
+    let service_discovery = state.service_discovery.clone();
+    let update_throttle = state.update_throttle.clone();
+    let advertisement_format = options.advertisement_format;
+    let decode_beacons = options.decode_beacons;
+    let decode_bthome = options.decode_bthome;
+    let bthome_bindkeys = state.bthome_bindkeys.clone();
+    let scan_report = state.scan_report.as_ref().map(Arc::downgrade);
+    if let (Some(report), Some(interval_ms)) = (&scan_report, options.scan_report_interval_ms) {
+        ScanReport::spawn_reporter(report.clone(), pid, interval_ms);
+    }
     let resource = ResourceArc::new(CentralRef(Arc::new(Mutex::new(state))));
 
-    // 🛠️ **Spawn event handler**
-    RUNTIME.spawn(async move {
-        debug!("🎧 Listening for adapter events...");
-        let mut events = match adapter.events().await {
-            Ok(events) => events,
-            Err(e) => {
-                debug!("❌ Failed to get adapter events: {:?}", e);
-                return;
-            }
-        };
-
-        while let Some(event) = events.next().await {
-            debug!("🔔 Adapter Event: {:?}", event);
-            if let Err(e) = event_sender_clone.send(event).await {
-                debug!("⚠️ Failed to forward event: {:?}", e);
-                break;
-            }
-        }
-        debug!("📴 Adapter event handler closed");
-    });
+    // 🛠️ **Spawn one event handler per adapter**, all feeding the same channel
+    let central = Arc::downgrade(&resource.0);
+    for (position, central_adapter) in adapters.into_iter().enumerate() {
+        spawn_adapter_events(
+            position,
+            central_adapter,
+            central.clone(),
+            event_sender.clone(),
+            pid,
+        );
+    }
+    drop(event_sender);
 
     // 🏷️ **Handle BLE Events**
     RUNTIME.spawn(async move {
         debug!("🎧 Listening for BLE events...");
         let mut receiver = event_receiver_clone.write().await;
 
-        while let Some(event) = receiver.recv().await {
+        while let Some((adapter_index, event)) = receiver.recv().await {
             let mut msg_env = OwnedEnv::new();
+            let adapter_clone = {
+                let adapters = central_adapters.lock().unwrap();
+                match adapters.iter().find(|a| a.index == adapter_index) {
+                    Some(central_adapter) => central_adapter.adapter.clone(),
+                    None => continue,
+                }
+            };
 
             match event {
                 CentralEvent::DeviceDiscovered(id) => {
                     let uuid = id.to_string();
+                    if !passes_filters(&adapter_clone, &scan_filter, &advertisement_filter, &uuid)
+                        .await
+                    {
+                        continue;
+                    }
                     info!("🔍 Device discovered: {}", uuid);
 
                     if let Some(peripheral) = find_peripheral_by_uuid(&adapter_clone, &uuid).await {
-                        // 🔄 Ensure service discovery
-                        if let Err(e) = peripheral.discover_services().await {
-                            warn!("⚠️ Failed to discover services for {}: {:?}", uuid, e);
+                        let properties_opt = peripheral.properties().await.ok().flatten();
+
+                        // 🔄 Service discovery only when the central's policy asks for it
+                        let should_discover = service_discovery
+                            .lock()
+                            .unwrap()
+                            .should_discover(&uuid, properties_opt.as_ref());
+                        if should_discover {
+                            if let Err(e) = peripheral.discover_services().await {
+                                warn!("⚠️ Failed to discover services for {}: {:?}", uuid, e);
+                            }
                         }
 
-                        let properties_opt = peripheral.properties().await.ok().flatten();
+                        if let Some(properties) = properties_opt.as_ref() {
+                            if let Some(rssi) = properties.rssi {
+                                cache_rssi(&uuid, rssi).await;
+                                adapter_rssi.lock().unwrap().record(
+                                    &uuid,
+                                    properties.address,
+                                    adapter_index,
+                                    rssi,
+                                );
+                            }
+                        }
 
-                        if let Some(rssi) = properties_opt.as_ref().and_then(|p| p.rssi) {
-                            cache_rssi(&uuid, rssi).await;
+                        if let Some(properties) = properties_opt.as_ref() {
+                            update_throttle.lock().unwrap().record(&uuid, properties);
                         }
 
                         let is_connected = peripheral.is_connected().await.unwrap_or(false);
@@ -106,14 +178,20 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
                             is_connected
                         );
 
+                        // 🗂️ Batch mode: reported with the next scan report
+                        if record_for_report(&scan_report, &uuid, properties_opt.as_ref()) {
+                            continue;
+                        }
+
                         match msg_env.send_and_clear(&pid, |env| {
                             (
                                 atoms::btleplug_peripheral_discovered(),
                                 uuid,
-                                properties_opt
-                                    .as_ref()
-                                    .map(|props| properties_to_map(env, props))
-                                    .unwrap_or_else(|| rustler::types::atom::nil().encode(env)),
+                                advertisement_format.encode(
+                                    env,
+                                    properties_opt.as_ref(),
+                                    adapter_index,
+                                ),
                             )
                                 .encode(env)
                         }) {
@@ -127,14 +205,42 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
 
                 CentralEvent::DeviceUpdated(id) => {
                     let uuid = id.to_string();
+                    if !passes_filters(&adapter_clone, &scan_filter, &advertisement_filter, &uuid)
+                        .await
+                    {
+                        continue;
+                    }
                     info!("🔄 Device updated: {}", uuid);
 
                     if let Some(peripheral) = find_peripheral_by_uuid(&adapter_clone, &uuid).await {
                         let properties_opt = peripheral.properties().await.ok().flatten();
                         let is_connected = peripheral.is_connected().await.unwrap_or(false);
 
-                        if let Some(rssi) = properties_opt.as_ref().and_then(|p| p.rssi) {
-                            cache_rssi(&uuid, rssi).await;
+                        if let Some(properties) = properties_opt.as_ref() {
+                            if let Some(rssi) = properties.rssi {
+                                cache_rssi(&uuid, rssi).await;
+                                adapter_rssi.lock().unwrap().record(
+                                    &uuid,
+                                    properties.address,
+                                    adapter_index,
+                                    rssi,
+                                );
+                            }
+                        }
+
+                        if record_for_report(&scan_report, &uuid, properties_opt.as_ref()) {
+                            continue;
+                        }
+
+                        // 🚰 Drop updates that carry nothing new
+                        if let Some(properties) = properties_opt.as_ref() {
+                            if !update_throttle
+                                .lock()
+                                .unwrap()
+                                .should_emit(&uuid, properties)
+                            {
+                                continue;
+                            }
                         }
 
                         debug!(
@@ -147,10 +253,11 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
                             (
                                 atoms::btleplug_peripheral_updated(),
                                 uuid,
-                                properties_opt
-                                    .as_ref()
-                                    .map(|props| properties_to_map(env, props))
-                                    .unwrap_or_else(|| rustler::types::atom::nil().encode(env)),
+                                advertisement_format.encode(
+                                    env,
+                                    properties_opt.as_ref(),
+                                    adapter_index,
+                                ),
                             )
                                 .encode(env)
                         }) {
@@ -176,6 +283,17 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
                 CentralEvent::DeviceDisconnected(id) => {
                     let uuid = id.to_string();
                     info!("❌ Device disconnected: {}", uuid);
+
+                    // 🔕 Notification streams survive reconnects, stop routing for this device
+                    let peripheral_ref = discovered_peripherals.lock().unwrap().get(&uuid).cloned();
+                    if let Some(peripheral_ref) = peripheral_ref {
+                        PeripheralState::stop_notifications(&peripheral_ref.0);
+                        PeripheralState::set_state(
+                            &peripheral_ref.0,
+                            PeripheralStateEnum::Disconnected,
+                        );
+                    }
+
                     match msg_env.send_and_clear(&pid, |env| {
                         (atoms::btleplug_peripheral_disconnected(), uuid).encode(env)
                     }) {
@@ -185,13 +303,12 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
                 }
 
                 CentralEvent::StateUpdate(state) => {
-                    debug!("🔄 Adapter state changed: {:?}", state);
+                    debug!("🔄 Adapter {} state changed: {:?}", adapter_index, state);
+                    let state = AdapterPowerState::from(state);
+                    adapter_state.send_modify(|states| states.set(adapter_index, state));
+
                     match msg_env.send_and_clear(&pid, |env| {
-                        (
-                            atoms::btleplug_adapter_status_update(),
-                            format!("{:?}", state),
-                        )
-                            .encode(env)
+                        (atoms::btleplug_adapter_status_update(), state).encode(env)
                     }) {
                         Ok(_) => debug!("✅ Sent state update message"),
                         Err(e) => debug!("⚠️ Failed to send state update message: {:?}", e),
@@ -202,6 +319,20 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
                     manufacturer_data,
                 } => {
                     let uuid = id.to_string();
+                    if !passes_filters(&adapter_clone, &scan_filter, &advertisement_filter, &uuid)
+                        .await
+                    {
+                        continue;
+                    }
+                    if decode_beacons {
+                        let beacons =
+                            BeaconFrame::from_advertised_manufacturer_data(&manufacturer_data);
+                        send_beacons(&adapter_clone, &pid, &uuid, beacons).await;
+                    }
+
+                    if record_advertisement_for_report(&adapter_clone, &scan_report, &uuid).await {
+                        continue;
+                    }
                     debug!(
                         "Manufacturer data from UUID: {} - Data: {:?}",
                         uuid, manufacturer_data
@@ -223,6 +354,30 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
                 }
                 CentralEvent::ServiceDataAdvertisement { id, service_data } => {
                     let uuid = id.to_string();
+                    if !passes_filters(&adapter_clone, &scan_filter, &advertisement_filter, &uuid)
+                        .await
+                    {
+                        continue;
+                    }
+                    if decode_beacons {
+                        let beacons = BeaconFrame::from_advertised_service_data(&service_data);
+                        send_beacons(&adapter_clone, &pid, &uuid, beacons).await;
+                    }
+
+                    if decode_bthome {
+                        send_bthome_readings(
+                            &adapter_clone,
+                            &pid,
+                            &uuid,
+                            &service_data,
+                            &bthome_bindkeys,
+                        )
+                        .await;
+                    }
+
+                    if record_advertisement_for_report(&adapter_clone, &scan_report, &uuid).await {
+                        continue;
+                    }
                     debug!(
                         "Service data from UUID: {} - Data: {:?}",
                         uuid, service_data
@@ -251,6 +406,11 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
                 }
                 CentralEvent::ServicesAdvertisement { id, services } => {
                     let uuid = id.to_string();
+                    if !passes_filters(&adapter_clone, &scan_filter, &advertisement_filter, &uuid)
+                        .await
+                    {
+                        continue;
+                    }
 
                     // ✅ Clone services BEFORE consuming it
                     let services_clone = services.clone();
@@ -264,6 +424,11 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
 
                     let mut cache = DISCOVERED_SERVICES.write().await;
                     cache.insert(uuid.clone(), service_uuids.clone());
+                    drop(cache);
+
+                    if record_advertisement_for_report(&adapter_clone, &scan_report, &uuid).await {
+                        continue;
+                    }
 
                     match msg_env.send_and_clear(&pid, |env| {
                         (
@@ -288,6 +453,99 @@ pub fn create_central(env: Env, pid: LocalPid) -> Result<ResourceArc<CentralRef>
     Ok(resource)
 }
 
+/// 📍 **Send `{:btleplug_beacon, peripheral_id, beacon}` per decoded frame**, tagged with the RSSI
+async fn send_beacons(
+    adapter: &Adapter,
+    pid: &LocalPid,
+    target_uuid: &str,
+    beacons: Vec<BeaconFrame>,
+) {
+    if beacons.is_empty() {
+        return;
+    }
+
+    let rssi = get_peripheral_properties(adapter, target_uuid)
+        .await
+        .and_then(|(_, properties)| properties.rssi);
+
+    let mut msg_env = OwnedEnv::new();
+    for beacon in beacons {
+        debug!("📍 Beacon from {}: {:?}", target_uuid, beacon);
+        match msg_env.send_and_clear(pid, |env| {
+            let beacon = beacon.encode(env);
+            let beacon = beacon.map_put(atoms::rssi(), rssi).unwrap_or(beacon);
+            (atoms::btleplug_beacon(), target_uuid, beacon).encode(env)
+        }) {
+            Ok(_) => debug!("✅ Sent beacon message"),
+            Err(e) => debug!("⚠️ Failed to send beacon message: {:?}", e),
+        }
+    }
+}
+
+/// 🏠 **Send `{:btleplug_sensor_reading, peripheral_id, reading}` for BTHome service data**
+async fn send_bthome_readings(
+    adapter: &Adapter,
+    pid: &LocalPid,
+    target_uuid: &str,
+    service_data: &HashMap<Uuid, Vec<u8>>,
+    bthome_bindkeys: &Arc<Mutex<HashMap<String, Bindkey>>>,
+) {
+    let Some(data) = service_data
+        .iter()
+        .find_map(|(uuid, data)| is_bthome_uuid(uuid).then_some(data))
+    else {
+        return;
+    };
+
+    // The MAC is part of the nonce, and also a key for the bindkey
+    let address = get_peripheral_properties(adapter, target_uuid)
+        .await
+        .map(|(_, properties)| properties.address);
+
+    let bindkey = {
+        let bindkeys = bthome_bindkeys.lock().unwrap();
+        bindkeys
+            .get(&target_uuid.to_uppercase())
+            .or_else(|| address.and_then(|a| bindkeys.get(&a.to_string().to_uppercase())))
+            .copied()
+    };
+
+    let reading = match decode_bthome_payload(data, address, bindkey.as_ref()) {
+        Ok(reading) => reading,
+        Err(e) => {
+            debug!(
+                "⚠️ Could not decode BTHome data from {}: {:?}",
+                target_uuid, e
+            );
+            return;
+        }
+    };
+
+    let mut msg_env = OwnedEnv::new();
+    match msg_env.send_and_clear(pid, |env| {
+        (atoms::btleplug_sensor_reading(), target_uuid, &reading).encode(env)
+    }) {
+        Ok(_) => debug!("✅ Sent sensor reading message"),
+        Err(e) => debug!("⚠️ Failed to send sensor reading message: {:?}", e),
+    }
+}
+
+/// 🗂️ **Batch mode: fold an advertisement event into the next scan report**
+async fn record_advertisement_for_report(
+    adapter: &Adapter,
+    scan_report: &Option<Weak<Mutex<ScanReport>>>,
+    target_uuid: &str,
+) -> bool {
+    if scan_report.is_none() {
+        return false;
+    }
+
+    let properties = get_peripheral_properties(adapter, target_uuid)
+        .await
+        .map(|(_, properties)| properties);
+    record_for_report(scan_report, target_uuid, properties.as_ref())
+}
+
 /// 🔎 **Find a Peripheral by UUID Using Adapter's Live Data**
 async fn find_peripheral_by_uuid(
     adapter: &Adapter,
@@ -299,80 +557,247 @@ async fn find_peripheral_by_uuid(
         .find(|p| p.id().to_string() == target_uuid)
 }
 
+/// 🚦 **Check a peripheral against the scan filter and the advertisement filter**
+async fn passes_filters(
+    adapter: &Adapter,
+    scan_filter: &Arc<Mutex<ScanFilter>>,
+    advertisement_filter: &Arc<Mutex<AdvertisementFilter>>,
+    target_uuid: &str,
+) -> bool {
+    let scan_filter = scan_filter.lock().unwrap().clone();
+    let advertisement_filter = advertisement_filter.lock().unwrap().clone();
+    if scan_filter.services.is_empty() && advertisement_filter.is_empty() {
+        return true;
+    }
+
+    match get_peripheral_properties(adapter, target_uuid).await {
+        Some((_, properties)) => {
+            let matches = matches_scan_filter(&scan_filter, &properties)
+                && advertisement_filter.matches(target_uuid, &properties);
+            if !matches {
+                debug!("🚫 Filtered out peripheral: {}", target_uuid);
+            }
+            matches
+        }
+        None => false,
+    }
+}
+
+/// 🛑 **Notify the owner that a scan session ended**
+fn send_scan_stopped(pid: &LocalPid, session_id: u64, reason: Atom) {
+    let mut msg_env = OwnedEnv::new();
+    if let Err(e) = msg_env.send_and_clear(pid, |env| {
+        (
+            atoms::btleplug_scan_stopped(),
+            ScanStoppedInfo { session_id, reason },
+        )
+            .encode(env)
+    }) {
+        warn!("⚠️ Failed to send scan stopped message: {:?}", e);
+    }
+}
+
 #[rustler::nif]
 pub fn start_scan(
     env: Env,
     resource: ResourceArc<CentralRef>,
     duration_ms: u64,
+    options: ScanOptions,
 ) -> Result<ResourceArc<CentralRef>, RustlerError> {
     let resource_arc = resource.0.clone();
-    let resource_arc_stop = resource_arc.clone();
 
     let env_pid = env.pid();
 
+    let (adapters, pid, scan_state, scan_filter) = {
+        let central_state = resource_arc.lock().unwrap();
+        let adapters = central_state.adapters.lock().unwrap().clone();
+        let mut scan_filter = central_state.scan_filter.lock().unwrap();
+        *scan_filter = options.filter.clone();
+        (
+            adapters,
+            central_state.pid,
+            central_state.scan.clone(),
+            scan_filter.clone(),
+        )
+    };
+
+    let (session_id, replaced) =
+        scan_state
+            .lock()
+            .unwrap()
+            .begin(options.continuous, duration_ms, system_time_us());
+
+    info!(
+        "Starting BLE scan session {} ({}), caller pid: {:?}, state pid: {:?}",
+        session_id,
+        if options.continuous {
+            "continuous".to_string()
+        } else {
+            format!("{} ms", duration_ms)
+        },
+        env_pid.as_c_arg(),
+        pid.as_c_arg()
+    );
+
+    if let Some(replaced) = replaced {
+        debug!("🔁 Scan session {} replaced by {}", replaced.id, session_id);
+        send_scan_stopped(&pid, replaced.id, atoms::replaced());
+    }
+
     RUNTIME.spawn(async move {
-        let mut msg_env = OwnedEnv::new();
-        // let env_pid_str = pid.as_c_arg();
+        info!("🚦 Scan filter services: {:?}", scan_filter.services);
 
-        let adapter = {
-            let central_state = resource_arc.lock().unwrap();
-            central_state.adapter.clone()
-        };
+        if let Err(e) = start_scan_all(&adapters, scan_filter).await {
+            warn!("Failed to start scan: {:?}", e);
+            if scan_state.lock().unwrap().end(session_id).is_some() {
+                send_scan_stopped(&pid, session_id, atoms::error());
+            }
+            return;
+        }
 
-        let pid = {
-            let central_state = resource_arc.lock().unwrap();
-            central_state.pid
+        // `stop_scan` may have run while the adapter was starting up
+        let session_info = {
+            let scan = scan_state.lock().unwrap();
+            match scan.active.as_ref() {
+                Some(session) if session.id == session_id => Some(session.info()),
+                _ => None,
+            }
         };
 
-        info!(
-            "Starting BLE scan for {} ms..., caller pid: {:?}, state pid: {:?}",
-            duration_ms,
-            env_pid.as_c_arg(),
-            pid.as_c_arg()
-        );
-
-        if let Err(e) = adapter.start_scan(ScanFilter::default()).await {
-            warn!("Failed to start scan: {:?}", e);
+        let Some(session_info) = session_info else {
+            debug!("⚠️ Scan session {} ended before it started", session_id);
+            if scan_state.lock().unwrap().active.is_none() {
+                stop_scan_all(&adapters).await;
+            }
             return;
-        }
+        };
+
+        let mut msg_env = OwnedEnv::new();
         msg_env.send_and_clear(&pid, |env| {
-            (
-                atoms::btleplug_scan_started(),
-                format!("Scan started: {:?} ms", duration_ms),
-            )
-                .encode(env)
+            (atoms::btleplug_scan_started(), session_info).encode(env)
         });
 
-        debug!("Scan started successfully");
+        debug!("Scan session {} started successfully", session_id);
 
-        // Wait for the specified duration
-        sleep(Duration::from_millis(duration_ms)).await;
-
-        // Stop the scan after timeout
-        let adapter = {
-            let central_state = resource_arc_stop.lock().unwrap();
-            central_state.adapter.clone()
-        };
-
-        if let Err(e) = adapter.stop_scan().await {
-            warn!("Failed to stop scan after timeout: {:?}", e);
+        if options.continuous {
             return;
         }
 
-        msg_env.send_and_clear(&pid, |env| {
-            (
-                atoms::btleplug_scan_stopped(),
-                format!("Scan stopped after timeout: {:?} ms", duration_ms),
-            )
-                .encode(env)
+        let timer_scan_state = scan_state.clone();
+        let timer = RUNTIME.spawn(async move {
+            // Wait for the specified duration
+            sleep(Duration::from_millis(duration_ms)).await;
+
+            if timer_scan_state.lock().unwrap().end(session_id).is_none() {
+                return;
+            }
+
+            // Stop the scan after timeout
+            stop_scan_all(&adapters).await;
+
+            send_scan_stopped(&pid, session_id, atoms::timeout());
+            debug!(
+                "Scan session {} stopped automatically after {} ms",
+                session_id, duration_ms
+            );
         });
 
-        debug!("Scan stopped automatically after {} ms", duration_ms);
+        scan_state.lock().unwrap().set_stop_timer(session_id, timer);
     });
 
     Ok(resource)
 }
 
+/// 🔎 **Report whether a scan session is running**
+#[rustler::nif]
+pub fn scan_status(resource: ResourceArc<CentralRef>) -> Result<ScanStatus, RustlerError> {
+    let scan_state = resource.0.lock().unwrap().scan.clone();
+    let scan = scan_state.lock().unwrap();
+
+    Ok(match scan.active.as_ref() {
+        Some(session) => ScanStatus {
+            scanning: true,
+            session_id: Some(session.id),
+            continuous: session.continuous,
+            duration_ms: session.duration_ms,
+            started_at: Some(session.started_at),
+        },
+        None => ScanStatus {
+            scanning: false,
+            session_id: None,
+            continuous: false,
+            duration_ms: None,
+            started_at: None,
+        },
+    })
+}
+
+#[rustler::nif]
+pub fn set_advertisement_filter(
+    resource: ResourceArc<CentralRef>,
+    filter: AdvertisementFilter,
+) -> Result<ResourceArc<CentralRef>, RustlerError> {
+    info!("🚦 Updating advertisement filter: {:?}", filter);
+
+    {
+        let central_state = resource.0.lock().unwrap();
+        *central_state.advertisement_filter.lock().unwrap() = filter;
+    }
+
+    Ok(resource)
+}
+
+/// 🚰 **Replace the `btleplug_peripheral_updated` throttle**, `nil` turns it off
+#[rustler::nif]
+pub fn set_update_throttle(
+    resource: ResourceArc<CentralRef>,
+    config: Option<ThrottleConfig>,
+) -> Result<ResourceArc<CentralRef>, RustlerError> {
+    info!("🚰 Updating update throttle: {:?}", config);
+
+    {
+        let central_state = resource.0.lock().unwrap();
+        central_state
+            .update_throttle
+            .lock()
+            .unwrap()
+            .set_config(config);
+    }
+
+    Ok(resource)
+}
+
+/// 🔑 **Register the BTHome bindkey of a device** (address or peripheral id), `nil` removes it
+#[rustler::nif]
+pub fn set_bthome_bindkey(
+    resource: ResourceArc<CentralRef>,
+    address: String,
+    bindkey: Option<Bindkey>,
+) -> Result<ResourceArc<CentralRef>, RustlerError> {
+    info!("🔑 Updating BTHome bindkey for {}", address);
+
+    {
+        let central_state = resource.0.lock().unwrap();
+        let mut bindkeys = central_state.bthome_bindkeys.lock().unwrap();
+        match bindkey {
+            Some(bindkey) => bindkeys.insert(address.to_uppercase(), bindkey),
+            None => bindkeys.remove(&address.to_uppercase()),
+        };
+    }
+
+    Ok(resource)
+}
+
+/// 📊 **Number of suppressed updates, in total and per peripheral**
+#[rustler::nif]
+pub fn update_throttle_stats(
+    resource: ResourceArc<CentralRef>,
+) -> Result<ThrottleStats, RustlerError> {
+    let central_state = resource.0.lock().unwrap();
+    let stats = central_state.update_throttle.lock().unwrap().stats();
+    Ok(stats)
+}
+
 #[rustler::nif]
 pub fn stop_scan(
     env: Env,
@@ -380,19 +805,22 @@ pub fn stop_scan(
 ) -> Result<ResourceArc<CentralRef>, RustlerError> {
     debug!("Stopping BLE scan...");
 
-    let resource_arc = resource.0.clone();
+    let (adapters, pid, session) = {
+        let central_state = resource.0.lock().unwrap();
+        let session = central_state.scan.lock().unwrap().take();
+        let adapters = central_state.adapters.lock().unwrap().clone();
+        (adapters, central_state.pid, session)
+    };
 
     RUNTIME.spawn(async move {
-        let adapter = {
-            let central_state = resource_arc.lock().unwrap();
-            central_state.adapter.clone()
-        };
+        stop_scan_all(&adapters).await;
 
-        if let Err(e) = adapter.stop_scan().await {
-            warn!("Failed to stop scan: {:?}", e);
-            return;
+        if let Some(session) = session {
+            send_scan_stopped(&pid, session.id, atoms::stopped());
+            debug!("Scan session {} stopped successfully", session.id);
+        } else {
+            debug!("Scan stopped successfully");
         }
-        debug!("Scan stopped successfully");
     });
     Ok(resource)
 }
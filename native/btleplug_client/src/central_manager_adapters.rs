@@ -0,0 +1,466 @@
+use crate::atoms;
+use crate::central_manager_options::{AdapterSelection, AdapterSelector};
+use crate::central_manager_state::CentralRef;
+use crate::RUNTIME;
+
+use log::{debug, info, warn};
+use rustler::{
+    Atom, Decoder, Encoder, Env, Error as RustlerError, NifMap, NifResult, ResourceArc, Term,
+};
+
+use btleplug::api::{BDAddr, Central, CentralEvent, CentralState, Manager as _, ScanFilter};
+use btleplug::platform::{Adapter, Manager};
+use std::collections::HashMap;
+use tokio::time::{timeout, Duration};
+
+/// 📻 **Adapter as listed by `list_adapters`**
+#[derive(NifMap, Debug, Clone)]
+pub struct AdapterListing {
+    pub index: usize,
+    pub info: String,
+}
+
+/// ❌ **No adapter matched the selector**, encodes as
+/// `{:adapter_not_found, selector, [info]}`
+#[derive(Debug, Clone)]
+pub struct AdapterNotFound {
+    pub selector: AdapterSelector,
+    pub available: Vec<String>,
+}
+
+impl Encoder for AdapterNotFound {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let selector = match &self.selector {
+            AdapterSelector::Index(index) => index.encode(env),
+            AdapterSelector::Name(name) => name.encode(env),
+        };
+        (atoms::adapter_not_found(), selector, self.available.clone()).encode(env)
+    }
+}
+
+impl From<AdapterNotFound> for RustlerError {
+    fn from(err: AdapterNotFound) -> Self {
+        RustlerError::Term(Box::new(err))
+    }
+}
+
+/// 📻 **Adapters with their `adapter_info()`**, in `Manager::adapters()` order
+pub async fn adapter_infos(adapters: &[Adapter]) -> Vec<String> {
+    let mut infos = Vec::with_capacity(adapters.len());
+    for adapter in adapters {
+        infos.push(
+            adapter
+                .adapter_info()
+                .await
+                .unwrap_or_else(|e| format!("unknown ({})", e)),
+        );
+    }
+    infos
+}
+
+/// 📻 **Adapter owned by a central**, `index` is its position in `list_adapters`
+#[derive(Debug, Clone)]
+pub struct CentralAdapter {
+    pub index: usize,
+    pub info: String,
+    pub adapter: Adapter,
+    /// How it was picked, used to find it again after a hot-unplug
+    pub selector: Option<AdapterSelector>,
+}
+
+/// 🏷️ **Central event tagged with the `list_adapters` index of the adapter that saw it**
+pub type AdapterEvent = (usize, CentralEvent);
+
+fn resolve_selector(selector: Option<&AdapterSelector>, infos: &[String]) -> Option<usize> {
+    match selector {
+        None => (!infos.is_empty()).then_some(0),
+        Some(AdapterSelector::Index(index)) => (*index < infos.len()).then_some(*index),
+        Some(AdapterSelector::Name(name)) => {
+            let name = name.to_lowercase();
+            infos
+                .iter()
+                .position(|info| info.to_lowercase().contains(&name))
+        }
+    }
+}
+
+/// 🎯 **Pick the adapters of a central**
+///
+/// `adapters` wins over `adapter`; without either the first adapter is used.
+pub async fn select_adapters(
+    adapters: Vec<Adapter>,
+    adapter: Option<&AdapterSelector>,
+    selection: Option<&AdapterSelection>,
+) -> Result<Vec<CentralAdapter>, AdapterNotFound> {
+    let infos = adapter_infos(&adapters).await;
+
+    let selectors: Vec<Option<&AdapterSelector>> = match selection {
+        Some(AdapterSelection::All) => Vec::new(),
+        Some(AdapterSelection::Only(selectors)) if !selectors.is_empty() => {
+            selectors.iter().map(Some).collect()
+        }
+        _ => vec![adapter],
+    };
+
+    let mut indexes: Vec<(usize, Option<AdapterSelector>)> = match selection {
+        Some(AdapterSelection::All) => (0..adapters.len()).map(|index| (index, None)).collect(),
+        _ => Vec::new(),
+    };
+    for selector in selectors {
+        let Some(index) = resolve_selector(selector, &infos) else {
+            warn!(
+                "❌ No adapter matches {:?}, available: {:?}",
+                selector, infos
+            );
+            return Err(AdapterNotFound {
+                selector: selector.cloned().unwrap_or(AdapterSelector::Index(0)),
+                available: infos,
+            });
+        };
+        if !indexes.iter().any(|(selected, _)| *selected == index) {
+            indexes.push((index, selector.cloned()));
+        }
+    }
+
+    Ok(indexes
+        .into_iter()
+        .map(|(index, selector)| {
+            info!("📻 Selected adapter {}: {}", index, infos[index]);
+            CentralAdapter {
+                index,
+                info: infos[index].clone(),
+                adapter: adapters[index].clone(),
+                selector,
+            }
+        })
+        .collect())
+}
+
+/// 🔌 **Power state of an adapter**, `:powered_on`, `:powered_off` or `:unknown`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdapterPowerState {
+    PoweredOn,
+    PoweredOff,
+    Unknown,
+}
+
+impl From<CentralState> for AdapterPowerState {
+    fn from(state: CentralState) -> Self {
+        match state {
+            CentralState::PoweredOn => AdapterPowerState::PoweredOn,
+            CentralState::PoweredOff => AdapterPowerState::PoweredOff,
+            CentralState::Unknown => AdapterPowerState::Unknown,
+        }
+    }
+}
+
+impl Encoder for AdapterPowerState {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            AdapterPowerState::PoweredOn => atoms::powered_on(),
+            AdapterPowerState::PoweredOff => atoms::powered_off(),
+            AdapterPowerState::Unknown => atoms::unknown(),
+        }
+        .encode(env)
+    }
+}
+
+impl<'a> Decoder<'a> for AdapterPowerState {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let state: Atom = term.decode()?;
+        if state == atoms::powered_on() {
+            Ok(AdapterPowerState::PoweredOn)
+        } else if state == atoms::powered_off() {
+            Ok(AdapterPowerState::PoweredOff)
+        } else if state == atoms::unknown() {
+            Ok(AdapterPowerState::Unknown)
+        } else {
+            Err(RustlerError::BadArg)
+        }
+    }
+}
+
+/// 🔌 **Power state of each adapter of a central**, by adapter index
+#[derive(Debug, Clone, Default)]
+pub struct AdapterStates(HashMap<usize, AdapterPowerState>);
+
+impl AdapterStates {
+    /// Ask every adapter for its current state
+    pub async fn query(adapters: &[CentralAdapter]) -> Self {
+        let mut states = AdapterStates::default();
+        for adapter in adapters {
+            let state = match adapter.adapter.adapter_state().await {
+                Ok(state) => state.into(),
+                Err(e) => {
+                    warn!("⚠️ Adapter {} state unavailable: {:?}", adapter.index, e);
+                    AdapterPowerState::Unknown
+                }
+            };
+            states.set(adapter.index, state);
+        }
+        states
+    }
+
+    pub fn set(&mut self, adapter: usize, state: AdapterPowerState) {
+        self.0.insert(adapter, state);
+    }
+
+    pub fn remove(&mut self, adapter: usize) {
+        self.0.remove(&adapter);
+    }
+
+    pub fn merge(&mut self, other: AdapterStates) {
+        self.0.extend(other.0);
+    }
+
+    /// `:powered_on` as soon as one adapter is usable, `:powered_off` when all are off
+    pub fn combined(&self) -> AdapterPowerState {
+        if self.0.values().any(|s| *s == AdapterPowerState::PoweredOn) {
+            AdapterPowerState::PoweredOn
+        } else if !self.0.is_empty() && self.0.values().all(|s| *s == AdapterPowerState::PoweredOff)
+        {
+            AdapterPowerState::PoweredOff
+        } else {
+            AdapterPowerState::Unknown
+        }
+    }
+}
+
+/// 📶 **Last RSSI of each device, per adapter index**
+///
+/// Every adapter has its own peripheral id for a device, so RSSI is keyed by the BD
+/// address and only falls back to the peripheral id while the address is unknown.
+#[derive(Debug, Default)]
+pub struct AdapterRssi {
+    by_device: HashMap<DeviceKey, HashMap<usize, i16>>,
+    addresses: HashMap<String, BDAddr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum DeviceKey {
+    Address(BDAddr),
+    Id(String),
+}
+
+impl AdapterRssi {
+    pub fn record(&mut self, peripheral_id: &str, address: BDAddr, adapter: usize, rssi: i16) {
+        let key = if address == BDAddr::default() {
+            DeviceKey::Id(peripheral_id.to_string())
+        } else {
+            self.addresses.insert(peripheral_id.to_string(), address);
+            DeviceKey::Address(address)
+        };
+        self.by_device.entry(key).or_default().insert(adapter, rssi);
+    }
+
+    /// BD address last advertised under a peripheral id or given as `device`
+    pub fn address(&self, device: &str) -> Option<BDAddr> {
+        self.addresses
+            .get(device)
+            .copied()
+            .or_else(|| device.parse().ok())
+            .filter(|address| *address != BDAddr::default())
+    }
+
+    fn key(&self, device: &str) -> DeviceKey {
+        self.address(device)
+            .map_or_else(|| DeviceKey::Id(device.to_string()), DeviceKey::Address)
+    }
+
+    /// RSSI per adapter of a device, given by peripheral id or BD address
+    pub fn get(&self, device: &str) -> HashMap<usize, i16> {
+        self.by_device
+            .get(&self.key(device))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Adapters ordered by how well they hear the device, unheard ones last
+    pub fn ranked(&self, device: &str, adapters: &[CentralAdapter]) -> Vec<CentralAdapter> {
+        let rssi = self.get(device);
+        let mut ranked = adapters.to_vec();
+        ranked.sort_by_key(|adapter| rank(&rssi, adapter.index));
+        ranked
+    }
+}
+
+fn rank(rssi: &HashMap<usize, i16>, adapter: usize) -> std::cmp::Reverse<Option<i16>> {
+    std::cmp::Reverse(rssi.get(&adapter).copied())
+}
+
+/// 🚦 **Start scanning on every adapter**, fails only when no adapter started
+pub async fn start_scan_all(
+    adapters: &[CentralAdapter],
+    filter: ScanFilter,
+) -> Result<(), btleplug::Error> {
+    let mut last_error = None;
+    let mut started = false;
+
+    for adapter in adapters {
+        match adapter.adapter.start_scan(filter.clone()).await {
+            Ok(()) => started = true,
+            Err(e) => {
+                warn!("⚠️ Adapter {} failed to start scan: {:?}", adapter.index, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match (started, last_error) {
+        (false, Some(e)) => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// 🛑 **Stop scanning on every adapter**
+pub async fn stop_scan_all(adapters: &[CentralAdapter]) {
+    for adapter in adapters {
+        if let Err(e) = adapter.adapter.stop_scan().await {
+            warn!("⚠️ Adapter {} failed to stop scan: {:?}", adapter.index, e);
+        }
+    }
+}
+
+/// 📻 **List the Bluetooth adapters** as `[%{index, info}]`
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn list_adapters() -> Result<Vec<AdapterListing>, RustlerError> {
+    RUNTIME.block_on(async {
+        let manager = Manager::new()
+            .await
+            .map_err(|e| RustlerError::Term(Box::new(format!("Manager error: {}", e))))?;
+        let adapters = manager
+            .adapters()
+            .await
+            .map_err(|e| RustlerError::Term(Box::new(format!("Adapter error: {}", e))))?;
+
+        Ok(adapter_infos(&adapters)
+            .await
+            .into_iter()
+            .enumerate()
+            .map(|(index, info)| AdapterListing { index, info })
+            .collect())
+    })
+}
+
+/// 📶 **Last RSSI of a device per adapter** as `%{adapter_index => rssi}`
+#[rustler::nif]
+pub fn adapter_rssi(
+    resource: ResourceArc<CentralRef>,
+    device: String,
+) -> Result<HashMap<usize, i16>, RustlerError> {
+    let central_state = resource.0.lock().unwrap();
+    let rssi = central_state.adapter_rssi.lock().unwrap().get(&device);
+    Ok(rssi)
+}
+
+/// 🔌 **Current power state of the central's adapters**, see `AdapterStates::combined`
+#[rustler::nif]
+pub fn adapter_state(resource: ResourceArc<CentralRef>) -> Result<AdapterPowerState, RustlerError> {
+    let central_state = resource.0.lock().unwrap();
+    let state = central_state.adapter_state.borrow().combined();
+    Ok(state)
+}
+
+/// ⏳ **Block until the adapters reach `state`**, `{:error, :timeout}` otherwise
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn wait_for_adapter_state(
+    resource: ResourceArc<CentralRef>,
+    state: AdapterPowerState,
+    timeout_ms: u64,
+) -> Result<AdapterPowerState, RustlerError> {
+    let mut states = resource.0.lock().unwrap().adapter_state.subscribe();
+    let (tx, rx) = tokio::sync::oneshot::channel::<bool>();
+
+    RUNTIME.spawn(async move {
+        let reached = timeout(
+            Duration::from_millis(timeout_ms),
+            states.wait_for(|states| states.combined() == state),
+        )
+        .await;
+        let _ = tx.send(matches!(reached, Ok(Ok(_))));
+    });
+
+    match rx.blocking_recv() {
+        Ok(true) => Ok(state),
+        _ => {
+            debug!("⏳ Adapters did not reach {:?} in {} ms", state, timeout_ms);
+            Err(RustlerError::Term(Box::new(atoms::timeout())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_adapter_selectors() {
+        let infos: Vec<String> = vec!["hci0 (onboard)".into(), "hci1 (Nordic dongle)".into()];
+
+        assert_eq!(resolve_selector(None, &infos), Some(0));
+        assert_eq!(resolve_selector(None, &[]), None);
+        assert_eq!(
+            resolve_selector(Some(&AdapterSelector::Index(1)), &infos),
+            Some(1)
+        );
+        assert_eq!(
+            resolve_selector(Some(&AdapterSelector::Index(2)), &infos),
+            None
+        );
+        assert_eq!(
+            resolve_selector(Some(&AdapterSelector::Name("NORDIC".into())), &infos),
+            Some(1)
+        );
+        assert_eq!(
+            resolve_selector(Some(&AdapterSelector::Name("hci".into())), &infos),
+            Some(0)
+        );
+        assert_eq!(
+            resolve_selector(Some(&AdapterSelector::Name("esp32".into())), &infos),
+            None
+        );
+    }
+
+    fn best_adapter(rssi: &AdapterRssi, device: &str) -> usize {
+        let heard = rssi.get(device);
+        let mut adapters = [0, 1, 2];
+        adapters.sort_by_key(|adapter| rank(&heard, *adapter));
+        adapters[0]
+    }
+
+    #[test]
+    fn one_device_seen_under_two_peripheral_ids() {
+        let address = BDAddr::from([0xC4, 0x4F, 0x33, 0x12, 0x34, 0x56]);
+        let mut rssi = AdapterRssi::default();
+        rssi.record("hci0/dev_C4_4F_33_12_34_56", address, 0, -80);
+        rssi.record("hci1/dev_C4_4F_33_12_34_56", address, 1, -52);
+
+        let both = HashMap::from([(0, -80), (1, -52)]);
+        assert_eq!(rssi.get("hci0/dev_C4_4F_33_12_34_56"), both);
+        assert_eq!(rssi.get("hci1/dev_C4_4F_33_12_34_56"), both);
+        assert_eq!(rssi.get("C4:4F:33:12:34:56"), both);
+        assert_eq!(rssi.get("c4:4f:33:12:34:56"), both);
+        assert_eq!(rssi.address("hci0/dev_C4_4F_33_12_34_56"), Some(address));
+
+        // Either id ranks the adapter hearing the device best first
+        assert_eq!(best_adapter(&rssi, "hci0/dev_C4_4F_33_12_34_56"), 1);
+        assert_eq!(best_adapter(&rssi, "C4:4F:33:12:34:56"), 1);
+
+        rssi.record("hci0/dev_C4_4F_33_12_34_56", address, 0, -40);
+        assert_eq!(best_adapter(&rssi, "hci1/dev_C4_4F_33_12_34_56"), 0);
+    }
+
+    #[test]
+    fn unknown_address_falls_back_to_the_peripheral_id() {
+        let mut rssi = AdapterRssi::default();
+        rssi.record("peripheral-a", BDAddr::default(), 0, -70);
+        rssi.record("peripheral-b", BDAddr::default(), 1, -60);
+
+        assert_eq!(rssi.get("peripheral-a"), HashMap::from([(0, -70)]));
+        assert_eq!(rssi.get("peripheral-b"), HashMap::from([(1, -60)]));
+        assert_eq!(rssi.get("00:00:00:00:00:00"), HashMap::new());
+        assert_eq!(rssi.address("peripheral-a"), None);
+        assert_eq!(best_adapter(&rssi, "peripheral-a"), 0);
+        assert_eq!(best_adapter(&rssi, "unheard"), 0);
+    }
+}
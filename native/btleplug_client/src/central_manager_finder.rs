@@ -1,16 +1,23 @@
 use crate::peripheral::PeripheralRef;
 use crate::peripheral::PeripheralState;
 
+use crate::atoms;
+use crate::central_manager_adapters::{start_scan_all, stop_scan_all, CentralAdapter};
 use crate::central_manager_state::*;
+use crate::peripheral::connect_internal;
+use crate::peripheral_utils::{GattError, OperationReply};
 
-use log::{info, warn};
-use rustler::{Env, Error as RustlerError, ResourceArc};
+use log::{debug, info, warn};
+use rustler::{Env, Error as RustlerError, Reference, ResourceArc};
 
-use btleplug::api::{Central, Peripheral};
+use btleplug::api::{Central, Peripheral as _, ScanFilter};
+use btleplug::platform::Peripheral;
 
 use crate::RUNTIME;
 use std::sync::{Arc, Mutex};
-use tokio::time::{timeout, Duration};
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+const ADDRESS_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 #[rustler::nif(schedule = "DirtyIo")]
 pub fn find_peripheral_by_name(
@@ -23,10 +30,11 @@ pub fn find_peripheral_by_name(
     let (tx, rx) = tokio::sync::oneshot::channel::<Result<ResourceArc<PeripheralRef>, String>>();
 
     let resource_arc = resource.0.clone();
-    let (adapter, pid, discovered_peripherals, event_receiver) = {
+    let (adapters, pid, discovered_peripherals, event_receiver) = {
         let central_state = resource_arc.lock().unwrap();
+        let adapters = central_state.adapters.lock().unwrap().clone();
         (
-            central_state.adapter.clone(),
+            adapters,
             central_state.pid,
             central_state.discovered_peripherals.clone(),
             central_state.event_receiver.clone(),
@@ -59,23 +67,16 @@ pub fn find_peripheral_by_name(
         //     return;
         // }
 
-        // **Scan for new peripherals**
-        let peripherals =
-            match timeout(Duration::from_millis(timeout_ms), adapter.peripherals()).await {
-                Ok(Ok(peripherals)) => peripherals,
-                Ok(Err(e)) => {
-                    warn!("❌ Failed to get peripherals: {:?}", e);
-                    let _ = tx.send(Err(format!("Failed to get peripherals: {}", e)));
-                    return;
-                }
-                Err(_) => {
-                    warn!("⏳ Timeout while fetching peripherals");
-                    let _ = tx.send(Err("Timeout while fetching peripherals".to_string()));
-                    return;
-                }
-            };
+        // **Scan for new peripherals** on every adapter of the central
+        let peripherals = match adapters_peripherals(&adapters, timeout_ms).await {
+            Ok(peripherals) => peripherals,
+            Err(e) => {
+                let _ = tx.send(Err(e));
+                return;
+            }
+        };
 
-        for peripheral in peripherals {
+        for (adapter_index, peripheral) in peripherals {
             let properties =
                 match timeout(Duration::from_millis(timeout_ms), peripheral.properties()).await {
                     Ok(Ok(Some(props))) => props,
@@ -93,8 +94,9 @@ pub fn find_peripheral_by_name(
                         ResourceArc::new(PeripheralRef(Arc::new(Mutex::new(peripheral_state))));
 
                     info!(
-                        "✅ Storing PeripheralRef in cache: {:?} (Peripheral Ptr: {:p})",
+                        "✅ Storing PeripheralRef in cache: {:?} via adapter {} (Peripheral Ptr: {:p})",
                         peripheral.id(),
+                        adapter_index,
                         Arc::as_ptr(&peripheral_ref.0)
                     );
 
@@ -132,10 +134,13 @@ pub fn find_peripheral(
     let (tx, rx) = tokio::sync::oneshot::channel::<Result<ResourceArc<PeripheralRef>, String>>();
 
     let resource_arc = resource.0.clone();
-    let (adapter, pid, discovered_peripherals, event_receiver) = {
+    let (adapters, address, pid, discovered_peripherals, event_receiver) = {
         let central_state = resource_arc.lock().unwrap();
+        let adapter_rssi = central_state.adapter_rssi.lock().unwrap();
+        let adapters = adapter_rssi.ranked(&uuid, &central_state.adapters.lock().unwrap());
         (
-            central_state.adapter.clone(),
+            adapters,
+            adapter_rssi.address(&uuid),
             central_state.pid,
             central_state.discovered_peripherals.clone(),
             central_state.event_receiver.clone(),
@@ -163,39 +168,43 @@ pub fn find_peripheral(
             }
         }
 
-        // **Step 2: Scan for new peripherals**
-        let peripherals =
-            match timeout(Duration::from_millis(timeout_ms), adapter.peripherals()).await {
-                Ok(Ok(peripherals)) => peripherals,
-                Ok(Err(e)) => {
-                    warn!("❌ Failed to get peripherals: {:?}", e);
-                    let _ = tx.send(Err(format!("Failed to get peripherals: {}", e)));
-                    return;
-                }
-                Err(_) => {
-                    warn!("⏳ Timeout while fetching peripherals");
-                    let _ = tx.send(Err("Timeout while fetching peripherals".to_string()));
-                    return;
-                }
-            };
+        // **Step 2: Scan for new peripherals**, the adapter hearing it best first.
+        // Each adapter has its own id for the device, so match the BD address too.
+        let peripherals = match adapters_peripherals(&adapters, timeout_ms).await {
+            Ok(peripherals) => peripherals,
+            Err(e) => {
+                let _ = tx.send(Err(e));
+                return;
+            }
+        };
 
-        for peripheral in peripherals {
-            if peripheral.id().to_string() == uuid_clone {
+        for (adapter_index, peripheral) in peripherals {
+            let matches = peripheral.id().to_string() == uuid_clone
+                || match address {
+                    Some(address) => matches!(
+                        timeout(Duration::from_millis(timeout_ms), peripheral.properties()).await,
+                        Ok(Ok(Some(props))) if props.address == address
+                    ),
+                    None => false,
+                };
+            if matches {
                 let peripheral_state =
                     PeripheralState::new(pid, Arc::new(peripheral.clone()), event_receiver.clone());
                 let peripheral_ref =
                     ResourceArc::new(PeripheralRef(Arc::new(Mutex::new(peripheral_state))));
 
                 info!(
-                    "✅ Storing PeripheralRef in cache: {:?} (Peripheral Ptr: {:p})",
+                    "✅ Storing PeripheralRef in cache: {:?} via adapter {} (Peripheral Ptr: {:p})",
                     peripheral.id(),
+                    adapter_index,
                     Arc::as_ptr(&peripheral_ref.0)
                 );
 
-                discovered_peripherals_clone
-                    .lock()
-                    .unwrap()
-                    .insert(peripheral.id().to_string(), peripheral_ref.clone());
+                {
+                    let mut cache = discovered_peripherals_clone.lock().unwrap();
+                    cache.insert(peripheral.id().to_string(), peripheral_ref.clone());
+                    cache.insert(uuid_clone.clone(), peripheral_ref.clone());
+                }
 
                 let _ = tx.send(Ok(peripheral_ref.clone()));
                 return;
@@ -216,3 +225,205 @@ pub fn find_peripheral(
         ))),
     }
 }
+
+/// 📻 **Peripherals known to each adapter**, tagged with the adapter index and kept in order
+///
+/// Fails only when no adapter answered.
+async fn adapters_peripherals(
+    adapters: &[CentralAdapter],
+    timeout_ms: u64,
+) -> Result<Vec<(usize, Peripheral)>, String> {
+    let mut peripherals = Vec::new();
+    let mut last_error = None;
+    let mut answered = false;
+
+    for central_adapter in adapters {
+        match timeout(
+            Duration::from_millis(timeout_ms),
+            central_adapter.adapter.peripherals(),
+        )
+        .await
+        {
+            Ok(Ok(found)) => {
+                answered = true;
+                peripherals.extend(found.into_iter().map(|p| (central_adapter.index, p)));
+            }
+            Ok(Err(e)) => {
+                warn!(
+                    "❌ Failed to get peripherals of adapter {}: {:?}",
+                    central_adapter.index, e
+                );
+                last_error = Some(format!("Failed to get peripherals: {}", e));
+            }
+            Err(_) => {
+                warn!(
+                    "⏳ Timeout while fetching peripherals of adapter {}",
+                    central_adapter.index
+                );
+                last_error = Some("Timeout while fetching peripherals".to_string());
+            }
+        }
+    }
+
+    match (answered, last_error) {
+        (false, Some(e)) => Err(e),
+        _ => Ok(peripherals),
+    }
+}
+
+/// 🎯 **Connect to a device by address or peripheral id**, scanning for it when unknown
+///
+/// Returns an operation reference. Phases arrive as `{:btleplug_op_progress, ref, phase}`
+/// (`:scanning`, `{:found, peripheral}`, `:connecting`), then `btleplug_op_result`.
+#[rustler::nif]
+pub fn connect_by_address<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<CentralRef>,
+    address: String,
+    scan_timeout_ms: u64,
+    timeout_ms: u64,
+) -> Result<Reference<'a>, RustlerError> {
+    let env_pid = env.pid();
+    let (adapters, pid, discovered_peripherals, event_receiver, scan_state) = {
+        let central_state = resource.0.lock().unwrap();
+        let adapters = central_state
+            .adapter_rssi
+            .lock()
+            .unwrap()
+            .ranked(&address, &central_state.adapters.lock().unwrap());
+        (
+            adapters,
+            central_state.pid,
+            central_state.discovered_peripherals.clone(),
+            central_state.event_receiver.clone(),
+            central_state.scan.clone(),
+        )
+    };
+    let (reply, op_ref) = OperationReply::new(env, pid);
+
+    RUNTIME.spawn(async move {
+        info!(
+            "🎯 Connecting by address: {}, caller pid: {:?}",
+            address,
+            env_pid.as_c_arg()
+        );
+
+        let cached = discovered_peripherals
+            .lock()
+            .unwrap()
+            .get(&address)
+            .cloned();
+
+        let peripheral_ref = match cached {
+            Some(peripheral_ref) => peripheral_ref,
+            None => {
+                let found = match find_by_address(&adapters, &address, timeout_ms).await {
+                    Some(found) => Some(found),
+                    None => {
+                        reply.progress(atoms::scanning());
+                        scan_for_address(
+                            &adapters,
+                            &address,
+                            scan_timeout_ms,
+                            timeout_ms,
+                            &scan_state,
+                        )
+                        .await
+                    }
+                };
+
+                let Some((adapter_index, peripheral)) = found else {
+                    warn!("❌ No peripheral with address {} found", address);
+                    reply.send(Err(GattError::PeripheralNotFound));
+                    return;
+                };
+
+                // Found by BD address, but the cache may know it under its peripheral id
+                discovered_peripherals
+                    .lock()
+                    .unwrap()
+                    .entry(peripheral.id().to_string())
+                    .or_insert_with(|| {
+                        info!(
+                            "✅ Storing PeripheralRef in cache: {:?} via adapter {}",
+                            peripheral.id(),
+                            adapter_index
+                        );
+                        let peripheral_state =
+                            PeripheralState::new(pid, Arc::new(peripheral.clone()), event_receiver);
+                        ResourceArc::new(PeripheralRef(Arc::new(Mutex::new(peripheral_state))))
+                    })
+                    .clone()
+            }
+        };
+
+        reply.progress((atoms::found(), peripheral_ref.clone()));
+        reply.progress(atoms::connecting());
+        reply.send(connect_internal(&peripheral_ref.0, env_pid, timeout_ms).await);
+    });
+
+    Ok(op_ref)
+}
+
+/// 🔍 **Peripheral already known to an adapter** whose id or address is `address`
+async fn find_by_address(
+    adapters: &[CentralAdapter],
+    address: &str,
+    timeout_ms: u64,
+) -> Option<(usize, Peripheral)> {
+    let peripherals = adapters_peripherals(adapters, timeout_ms).await.ok()?;
+
+    for (adapter_index, peripheral) in peripherals {
+        if peripheral.id().to_string().eq_ignore_ascii_case(address) {
+            return Some((adapter_index, peripheral));
+        }
+
+        let properties =
+            match timeout(Duration::from_millis(timeout_ms), peripheral.properties()).await {
+                Ok(Ok(Some(props))) => props,
+                _ => continue,
+            };
+        if properties.address.to_string().eq_ignore_ascii_case(address) {
+            return Some((adapter_index, peripheral));
+        }
+    }
+
+    None
+}
+
+/// 📡 **Scan until `address` shows up** or `scan_timeout_ms` passes
+///
+/// A running scan session of the central is only polled, so its filter stays in place.
+/// Otherwise an unfiltered scan is started and stopped again afterwards.
+async fn scan_for_address(
+    adapters: &[CentralAdapter],
+    address: &str,
+    scan_timeout_ms: u64,
+    timeout_ms: u64,
+    scan_state: &Arc<Mutex<ScanState>>,
+) -> Option<(usize, Peripheral)> {
+    let session_active = scan_state.lock().unwrap().active.is_some();
+    if session_active {
+        debug!("📡 Scan session running, polling for {}", address);
+    } else if let Err(e) = start_scan_all(adapters, ScanFilter::default()).await {
+        warn!("❌ Failed to start scan for {}: {:?}", address, e);
+        return None;
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(scan_timeout_ms);
+    let mut found = None;
+    while Instant::now() < deadline {
+        sleep(ADDRESS_POLL_INTERVAL).await;
+        found = find_by_address(adapters, address, timeout_ms).await;
+        if found.is_some() {
+            break;
+        }
+    }
+
+    // A session started meanwhile keeps the adapters scanning
+    if !session_active && scan_state.lock().unwrap().active.is_none() {
+        stop_scan_all(adapters).await;
+    }
+
+    found
+}
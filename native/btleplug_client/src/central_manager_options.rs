@@ -0,0 +1,226 @@
+use crate::advertisement::AdvertisementFormat;
+use crate::advertisement_throttle::ThrottleConfig;
+use crate::atoms;
+
+use rustler::{Atom, Decoder, Error as RustlerError, NifResult, Term};
+
+use btleplug::api::bleuuid::uuid_from_u16;
+use btleplug::api::{PeripheralProperties, ScanFilter};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// 🔑 **Look up an optional atom key in an options map** (`nil` counts as missing)
+pub fn get_option<'a, T: Decoder<'a>>(options: Term<'a>, key: &str) -> NifResult<Option<T>> {
+    if !options.is_map() {
+        return Err(RustlerError::BadArg);
+    }
+
+    let key = Atom::from_str(options.get_env(), key)?;
+    match options.map_get(key) {
+        Ok(value) if value.is_atom() && value.decode::<Atom>()? == rustler::types::atom::nil() => {
+            Ok(None)
+        }
+        Ok(value) => value.decode().map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+/// 🆔 **Parse a full UUID string or a 16-bit short form such as `"180d"`**
+pub fn parse_uuid(uuid: &str) -> NifResult<Uuid> {
+    if uuid.len() == 4 {
+        if let Ok(short) = u16::from_str_radix(uuid, 16) {
+            return Ok(uuid_from_u16(short));
+        }
+    }
+
+    Uuid::parse_str(uuid)
+        .map_err(|_| RustlerError::Term(Box::new((atoms::invalid_uuid(), uuid.to_string()))))
+}
+
+pub fn parse_uuids(uuids: &[String]) -> NifResult<Vec<Uuid>> {
+    uuids.iter().map(|uuid| parse_uuid(uuid)).collect()
+}
+
+/// ✅ **Options accepted by `start_scan`**
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    pub filter: ScanFilter,
+    /// Keep scanning until `stop_scan`, ignoring the duration
+    pub continuous: bool,
+}
+
+impl<'a> Decoder<'a> for ScanOptions {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let services: Vec<String> = get_option(term, "services")?.unwrap_or_default();
+
+        Ok(ScanOptions {
+            filter: ScanFilter {
+                services: parse_uuids(&services)?,
+            },
+            continuous: get_option(term, "continuous")?.unwrap_or(false),
+        })
+    }
+}
+
+/// 🔍 **When to run GATT service discovery for devices seen during a scan**
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ServiceDiscoveryPolicy {
+    #[default]
+    Never,
+    AllowListed(HashSet<String>),
+    Always,
+}
+
+impl ServiceDiscoveryPolicy {
+    pub fn should_discover(
+        &self,
+        peripheral_id: &str,
+        properties: Option<&PeripheralProperties>,
+    ) -> bool {
+        match self {
+            ServiceDiscoveryPolicy::Never => false,
+            ServiceDiscoveryPolicy::Always => true,
+            ServiceDiscoveryPolicy::AllowListed(allowed) => {
+                allowed.contains(&peripheral_id.to_uppercase())
+                    || properties.is_some_and(|props| {
+                        allowed.contains(&props.address.to_string().to_uppercase())
+                    })
+            }
+        }
+    }
+}
+
+impl<'a> Decoder<'a> for ServiceDiscoveryPolicy {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if let Ok(policy) = term.decode::<Atom>() {
+            return if policy == atoms::never() {
+                Ok(ServiceDiscoveryPolicy::Never)
+            } else if policy == atoms::always() {
+                Ok(ServiceDiscoveryPolicy::Always)
+            } else {
+                Err(RustlerError::BadArg)
+            };
+        }
+
+        let (tag, allowed): (Atom, Vec<String>) = term.decode()?;
+        if tag != atoms::allow_listed() {
+            return Err(RustlerError::BadArg);
+        }
+
+        Ok(ServiceDiscoveryPolicy::AllowListed(
+            allowed.into_iter().map(|id| id.to_uppercase()).collect(),
+        ))
+    }
+}
+
+/// 📻 **Which adapter a central uses**: index in `list_adapters` or a substring of its info
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdapterSelector {
+    Index(usize),
+    Name(String),
+}
+
+impl<'a> Decoder<'a> for AdapterSelector {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if let Ok(index) = term.decode::<usize>() {
+            return Ok(AdapterSelector::Index(index));
+        }
+        Ok(AdapterSelector::Name(term.decode()?))
+    }
+}
+
+/// 📻 **Several adapters for one central**: `:all` or a list of selectors
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdapterSelection {
+    All,
+    Only(Vec<AdapterSelector>),
+}
+
+impl<'a> Decoder<'a> for AdapterSelection {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if let Ok(all) = term.decode::<Atom>() {
+            return if all == atoms::all() {
+                Ok(AdapterSelection::All)
+            } else {
+                Err(RustlerError::BadArg)
+            };
+        }
+        Ok(AdapterSelection::Only(term.decode()?))
+    }
+}
+
+/// ✅ **Options accepted by `create_central`**
+#[derive(Debug, Clone, Default)]
+pub struct CentralOptions {
+    pub service_discovery: ServiceDiscoveryPolicy,
+    pub update_throttle: Option<ThrottleConfig>,
+    /// Batch advertisements into one `btleplug_scan_report` per window
+    pub scan_report_interval_ms: Option<u64>,
+    pub advertisement_format: AdvertisementFormat,
+    /// Emit `btleplug_beacon` for iBeacon / AltBeacon / Eddystone frames
+    pub decode_beacons: bool,
+    /// Emit `btleplug_sensor_reading` for BTHome v2 service data
+    pub decode_bthome: bool,
+    /// First adapter when not set
+    pub adapter: Option<AdapterSelector>,
+    /// Merge events of several adapters, takes precedence over `adapter`
+    pub adapters: Option<AdapterSelection>,
+}
+
+impl<'a> Decoder<'a> for CentralOptions {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(CentralOptions {
+            service_discovery: get_option(term, "service_discovery")?.unwrap_or_default(),
+            update_throttle: get_option(term, "update_throttle")?,
+            scan_report_interval_ms: get_option(term, "scan_report_interval_ms")?,
+            advertisement_format: get_option(term, "advertisement_format")?.unwrap_or_default(),
+            decode_beacons: get_option(term, "decode_beacons")?.unwrap_or(false),
+            decode_bthome: get_option(term, "decode_bthome")?.unwrap_or(false),
+            adapter: get_option(term, "adapter")?,
+            adapters: get_option(term, "adapters")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use btleplug::api::BDAddr;
+
+    const ID: &str = "hci0/dev_AA_BB_CC_DD_EE_FF";
+
+    fn advertisement() -> PeripheralProperties {
+        PeripheralProperties {
+            address: BDAddr::from([0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]),
+            ..PeripheralProperties::default()
+        }
+    }
+
+    fn allow_listed(ids: &[&str]) -> ServiceDiscoveryPolicy {
+        ServiceDiscoveryPolicy::AllowListed(ids.iter().map(|id| id.to_uppercase()).collect())
+    }
+
+    #[test]
+    fn never_and_always_ignore_the_device() {
+        assert_eq!(
+            ServiceDiscoveryPolicy::default(),
+            ServiceDiscoveryPolicy::Never
+        );
+        assert!(!ServiceDiscoveryPolicy::Never.should_discover(ID, Some(&advertisement())));
+        assert!(ServiceDiscoveryPolicy::Always.should_discover(ID, None));
+    }
+
+    #[test]
+    fn allow_list_matches_peripheral_id_or_address() {
+        assert!(allow_listed(&[&ID.to_lowercase()]).should_discover(ID, None));
+        assert!(allow_listed(&["aa:bb:cc:dd:ee:ff"]).should_discover(ID, Some(&advertisement())));
+    }
+
+    #[test]
+    fn allow_list_rejects_other_devices() {
+        // The address is only known from the advertisement
+        assert!(!allow_listed(&["aa:bb:cc:dd:ee:ff"]).should_discover(ID, None));
+        assert!(!allow_listed(&["11:22:33:44:55:66"]).should_discover(ID, Some(&advertisement())));
+        assert!(!allow_listed(&[]).should_discover(ID, Some(&advertisement())));
+    }
+}
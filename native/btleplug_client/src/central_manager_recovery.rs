@@ -0,0 +1,324 @@
+use crate::atoms;
+use crate::central_manager_adapters::{
+    adapter_infos, AdapterEvent, AdapterListing, AdapterStates, CentralAdapter,
+};
+use crate::central_manager_options::AdapterSelector;
+use crate::central_manager_state::CentralManagerState;
+use crate::RUNTIME;
+
+use log::{debug, info, warn};
+use rustler::{Encoder, LocalPid, OwnedEnv};
+
+use btleplug::api::{Central, Manager as _};
+use btleplug::platform::Manager;
+use futures::StreamExt;
+
+use std::sync::{Mutex, Weak};
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration};
+
+const RECOVERY_INITIAL_DELAY: Duration = Duration::from_millis(500);
+const RECOVERY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// ⏳ **Exponential backoff between recovery attempts**
+///
+/// Lives as long as the event handler, so an adapter whose event stream fails right
+/// after every recovery is retried less and less often instead of every 500ms.
+#[derive(Debug)]
+struct Backoff {
+    delay: Duration,
+}
+
+impl Backoff {
+    fn new() -> Self {
+        Backoff {
+            delay: RECOVERY_INITIAL_DELAY,
+        }
+    }
+
+    /// The delay to wait now, doubling the next one up to the maximum
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.delay;
+        self.delay = (delay * 2).min(RECOVERY_MAX_DELAY);
+        delay
+    }
+
+    fn reset(&mut self) {
+        self.delay = RECOVERY_INITIAL_DELAY;
+    }
+}
+
+/// 🎧 **Forward the events of one adapter into the central's channel**
+///
+/// When the event stream ends (dongle unplugged, bluetoothd restarted) the owner gets
+/// `btleplug_adapter_lost`, the adapter is looked up again with backoff and, once it is
+/// back, re-attached at the same `position` with `btleplug_adapter_recovered`. The backoff
+/// only starts over once a re-attached adapter has delivered an event.
+pub fn spawn_adapter_events(
+    position: usize,
+    mut adapter: CentralAdapter,
+    central: Weak<Mutex<CentralManagerState>>,
+    event_sender: mpsc::Sender<AdapterEvent>,
+    pid: LocalPid,
+) {
+    RUNTIME.spawn(async move {
+        let mut backoff = Backoff::new();
+        loop {
+            let index = adapter.index;
+            debug!("🎧 Listening for events of adapter {}...", index);
+
+            match adapter.adapter.events().await {
+                Ok(mut events) => {
+                    while let Some(event) = events.next().await {
+                        debug!("🔔 Adapter {} Event: {:?}", index, event);
+                        backoff.reset();
+                        if let Err(e) = event_sender.send((index, event)).await {
+                            debug!("⚠️ Failed to forward event: {:?}", e);
+                            return;
+                        }
+                    }
+                    debug!("📴 Adapter {} event stream ended", index);
+                }
+                Err(e) => debug!("❌ Failed to get events of adapter {}: {:?}", index, e),
+            }
+
+            if central.strong_count() == 0 {
+                debug!("📴 Adapter {} event handler closed", index);
+                return;
+            }
+
+            warn!("🔌 Adapter {} lost: {}", index, adapter.info);
+            send_adapter_message(&pid, atoms::btleplug_adapter_lost(), &adapter);
+
+            match recover(position, &adapter, &central, &mut backoff).await {
+                Some(recovered) => {
+                    info!(
+                        "🔌 Adapter {} recovered as {}: {}",
+                        index, recovered.index, recovered.info
+                    );
+                    send_adapter_message(&pid, atoms::btleplug_adapter_recovered(), &recovered);
+                    adapter = recovered;
+                }
+                None => {
+                    debug!("📴 Central dropped while adapter {} was lost", index);
+                    return;
+                }
+            }
+        }
+    });
+}
+
+fn send_adapter_message(pid: &LocalPid, tag: rustler::Atom, adapter: &CentralAdapter) {
+    let listing = AdapterListing {
+        index: adapter.index,
+        info: adapter.info.clone(),
+    };
+    let mut msg_env = OwnedEnv::new();
+    if let Err(e) = msg_env.send_and_clear(pid, |env| (tag, listing).encode(env)) {
+        debug!("⚠️ Failed to send adapter message: {:?}", e);
+    }
+}
+
+/// 🔁 **Retry until the lost adapter is back**, `None` once the central is gone
+async fn recover(
+    position: usize,
+    lost: &CentralAdapter,
+    central: &Weak<Mutex<CentralManagerState>>,
+    backoff: &mut Backoff,
+) -> Option<CentralAdapter> {
+    {
+        let central = central.upgrade()?;
+        let state = central.lock().unwrap();
+        state
+            .adapter_state
+            .send_modify(|states| states.remove(lost.index));
+    }
+
+    loop {
+        let delay = backoff.next_delay();
+        sleep(delay).await;
+
+        // Adapters attached at other positions must not be picked twice
+        let attached: Vec<String> = {
+            let central = central.upgrade()?;
+            let state = central.lock().unwrap();
+            let adapters = state.adapters.lock().unwrap();
+            adapters
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != position)
+                .map(|(_, adapter)| adapter.info.clone())
+                .collect()
+        };
+
+        match reattach(lost, &attached).await {
+            Some(adapter) => {
+                restore(position, &adapter, central).await?;
+                return Some(adapter);
+            }
+            None => {
+                debug!(
+                    "🔁 Adapter {} not back after {:?}, retrying",
+                    lost.index, delay
+                );
+            }
+        }
+    }
+}
+
+/// 🔍 **Find the lost adapter again**: same info first, then its original selector
+async fn reattach(lost: &CentralAdapter, attached: &[String]) -> Option<CentralAdapter> {
+    let manager = Manager::new().await.ok()?;
+    let adapters = manager.adapters().await.ok()?;
+    let infos = adapter_infos(&adapters).await;
+
+    let selector = lost
+        .selector
+        .clone()
+        .unwrap_or(AdapterSelector::Index(lost.index));
+    let index = reattach_index(&lost.info, &selector, &infos, attached)?;
+
+    Some(CentralAdapter {
+        index,
+        info: infos[index].clone(),
+        adapter: adapters[index].clone(),
+        selector: lost.selector.clone(),
+    })
+}
+
+/// 🎯 **Index of the lost adapter in a fresh listing**, skipping adapters already
+/// attached at other positions
+fn reattach_index(
+    info: &str,
+    selector: &AdapterSelector,
+    infos: &[String],
+    attached: &[String],
+) -> Option<usize> {
+    let free = |index: &usize| !attached.contains(&infos[*index]);
+    (0..infos.len())
+        .filter(free)
+        .find(|index| infos[*index] == info)
+        .or_else(|| {
+            (0..infos.len()).filter(free).find(|index| match selector {
+                AdapterSelector::Index(wanted) => index == wanted,
+                AdapterSelector::Name(name) => {
+                    infos[*index].to_lowercase().contains(&name.to_lowercase())
+                }
+            })
+        })
+}
+
+/// ♻️ **Put the re-attached adapter in place** and resume a running scan on it
+async fn restore(
+    position: usize,
+    adapter: &CentralAdapter,
+    central: &Weak<Mutex<CentralManagerState>>,
+) -> Option<()> {
+    let scan_filter = {
+        let central = central.upgrade()?;
+        let mut state = central.lock().unwrap();
+        if let Some(slot) = state.adapters.lock().unwrap().get_mut(position) {
+            *slot = adapter.clone();
+        }
+        if position == 0 {
+            state.adapter = adapter.adapter.clone();
+        }
+        let scanning = state.scan.lock().unwrap().active.is_some();
+        let scan_filter = state.scan_filter.lock().unwrap().clone();
+        scanning.then_some(scan_filter)
+    };
+
+    let states = AdapterStates::query(std::slice::from_ref(adapter)).await;
+    {
+        let central = central.upgrade()?;
+        let state = central.lock().unwrap();
+        state
+            .adapter_state
+            .send_modify(|current| current.merge(states));
+    }
+
+    if let Some(scan_filter) = scan_filter {
+        info!("🚦 Resuming scan on adapter {}", adapter.index);
+        if let Err(e) = adapter.adapter.start_scan(scan_filter).await {
+            warn!(
+                "⚠️ Failed to resume scan on adapter {}: {:?}",
+                adapter.index, e
+            );
+        }
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infos(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum_and_resets() {
+        let mut backoff = Backoff::new();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(500));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+
+        for _ in 0..10 {
+            backoff.next_delay();
+        }
+        assert_eq!(backoff.next_delay(), RECOVERY_MAX_DELAY);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), RECOVERY_INITIAL_DELAY);
+    }
+
+    #[test]
+    fn reattaches_the_same_adapter_even_at_a_new_index() {
+        let listing = infos(&["hci1 (usb)", "hci0 (usb)"]);
+        assert_eq!(
+            reattach_index("hci0 (usb)", &AdapterSelector::Index(0), &listing, &[]),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_selector() {
+        let listing = infos(&["hci0 (onboard)", "hci2 (Nordic dongle)"]);
+        let by_name = AdapterSelector::Name("nordic".to_string());
+        assert_eq!(
+            reattach_index("hci1 (Nordic dongle)", &by_name, &listing, &[]),
+            Some(1)
+        );
+        assert_eq!(
+            reattach_index("hci1 (usb)", &AdapterSelector::Index(0), &listing, &[]),
+            Some(0)
+        );
+        assert_eq!(
+            reattach_index("hci1 (usb)", &AdapterSelector::Index(5), &listing, &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn skips_adapters_attached_at_other_positions() {
+        let listing = infos(&["hci0 (usb)", "hci1 (usb)"]);
+        let attached = infos(&["hci0 (usb)"]);
+        let by_name = AdapterSelector::Name("usb".to_string());
+        assert_eq!(
+            reattach_index("hci9 (usb)", &by_name, &listing, &attached),
+            Some(1)
+        );
+        assert_eq!(
+            reattach_index(
+                "hci0 (usb)",
+                &AdapterSelector::Index(0),
+                &listing,
+                &attached
+            ),
+            None
+        );
+        assert_eq!(reattach_index("hci0 (usb)", &by_name, &[], &[]), None);
+    }
+}
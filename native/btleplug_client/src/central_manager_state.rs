@@ -1,15 +1,23 @@
+use crate::advertisement_filter::AdvertisementFilter;
+use crate::advertisement_throttle::UpdateThrottle;
+use crate::bthome::Bindkey;
+use crate::central_manager_adapters::{AdapterEvent, AdapterRssi, AdapterStates, CentralAdapter};
+use crate::central_manager_options::{CentralOptions, ServiceDiscoveryPolicy};
 use crate::peripheral::PeripheralRef;
+use crate::scan_report::ScanReport;
 
-use rustler::{LocalPid, ResourceArc};
+use rustler::{Atom, LocalPid, NifMap, ResourceArc};
 use std::collections::HashMap;
 
-use btleplug::api::CentralEvent;
+use btleplug::api::ScanFilter;
 use btleplug::platform::{Adapter, Manager};
 
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 
 lazy_static::lazy_static! {
     pub static ref DISCOVERED_SERVICES: RwLock<HashMap<String, Vec<String>>> = RwLock::new(HashMap::new());
@@ -21,32 +29,172 @@ lazy_static::lazy_static! {
    pub static ref RSSI_CACHE: RwLock<HashMap<String, Vec<(i64, i16)>>> = RwLock::new(HashMap::new());
 }
 
+/// 📡 **A running scan**, identified by a per-central session id
+#[derive(Debug)]
+pub struct ScanSession {
+    pub id: u64,
+    pub continuous: bool,
+    pub duration_ms: Option<u64>,
+    /// µs since the Unix epoch
+    pub started_at: i64,
+    pub stop_timer: Option<JoinHandle<()>>,
+}
+
+impl ScanSession {
+    /// Abort the pending auto-stop so it can't stop a newer scan
+    pub fn cancel_timer(&mut self) {
+        if let Some(timer) = self.stop_timer.take() {
+            timer.abort();
+        }
+    }
+
+    pub fn info(&self) -> ScanSessionInfo {
+        ScanSessionInfo {
+            session_id: self.id,
+            continuous: self.continuous,
+            duration_ms: self.duration_ms,
+            started_at: self.started_at,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ScanState {
+    pub last_session_id: u64,
+    pub active: Option<ScanSession>,
+}
+
+impl ScanState {
+    /// Open a new session, returning the one it replaces (timer already cancelled)
+    pub fn begin(
+        &mut self,
+        continuous: bool,
+        duration_ms: u64,
+        started_at: i64,
+    ) -> (u64, Option<ScanSession>) {
+        self.last_session_id += 1;
+        let replaced = self.take();
+
+        self.active = Some(ScanSession {
+            id: self.last_session_id,
+            continuous,
+            duration_ms: (!continuous).then_some(duration_ms),
+            started_at,
+            stop_timer: None,
+        });
+
+        (self.last_session_id, replaced)
+    }
+
+    pub fn is_current(&self, session_id: u64) -> bool {
+        self.active.as_ref().is_some_and(|s| s.id == session_id)
+    }
+
+    /// Close the session only if it is still the active one
+    pub fn end(&mut self, session_id: u64) -> Option<ScanSession> {
+        if self.is_current(session_id) {
+            self.take()
+        } else {
+            None
+        }
+    }
+
+    pub fn take(&mut self) -> Option<ScanSession> {
+        let mut session = self.active.take()?;
+        session.cancel_timer();
+        Some(session)
+    }
+
+    /// Attach the auto-stop timer; aborted right away if the session is already gone
+    pub fn set_stop_timer(&mut self, session_id: u64, timer: JoinHandle<()>) {
+        match self.active.as_mut() {
+            Some(session) if session.id == session_id => session.stop_timer = Some(timer),
+            _ => timer.abort(),
+        }
+    }
+}
+
+/// 📡 **Payload of `btleplug_scan_started`**
+#[derive(Debug, Clone, NifMap)]
+pub struct ScanSessionInfo {
+    pub session_id: u64,
+    pub continuous: bool,
+    pub duration_ms: Option<u64>,
+    pub started_at: i64,
+}
+
+/// 🛑 **Payload of `btleplug_scan_stopped`**, `reason` is `:timeout`, `:stopped`, `:replaced` or `:error`
+#[derive(Debug, Clone, NifMap)]
+pub struct ScanStoppedInfo {
+    pub session_id: u64,
+    pub reason: Atom,
+}
+
+/// 🔎 **Result of `scan_status`**
+#[derive(Debug, Clone, NifMap)]
+pub struct ScanStatus {
+    pub scanning: bool,
+    pub session_id: Option<u64>,
+    pub continuous: bool,
+    pub duration_ms: Option<u64>,
+    pub started_at: Option<i64>,
+}
+
 pub struct CentralRef(pub(crate) Arc<Mutex<CentralManagerState>>);
 
 pub struct CentralManagerState {
     pub pid: LocalPid,
+    /// First of `adapters`
     pub adapter: Adapter,
+    pub adapters: Arc<Mutex<Vec<CentralAdapter>>>,
+    pub adapter_rssi: Arc<Mutex<AdapterRssi>>,
+    /// Updated from `StateUpdate` events, watched by `wait_for_adapter_state`
+    pub adapter_state: watch::Sender<AdapterStates>,
     pub manager: Manager,
-    pub event_sender: mpsc::Sender<CentralEvent>,
-    pub event_receiver: Arc<RwLock<mpsc::Receiver<CentralEvent>>>,
+    pub event_sender: mpsc::Sender<AdapterEvent>,
+    pub event_receiver: Arc<RwLock<mpsc::Receiver<AdapterEvent>>>,
     pub discovered_peripherals: Arc<Mutex<HashMap<String, ResourceArc<PeripheralRef>>>>,
+    pub scan_filter: Arc<Mutex<ScanFilter>>,
+    pub advertisement_filter: Arc<Mutex<AdvertisementFilter>>,
+    pub service_discovery: Arc<Mutex<ServiceDiscoveryPolicy>>,
+    pub scan: Arc<Mutex<ScanState>>,
+    pub update_throttle: Arc<Mutex<UpdateThrottle>>,
+    /// Only set in batch report mode
+    pub scan_report: Option<Arc<Mutex<ScanReport>>>,
+    /// BTHome bindkeys by uppercase address / peripheral id
+    pub bthome_bindkeys: Arc<Mutex<HashMap<String, Bindkey>>>,
 }
 
 impl CentralManagerState {
     pub fn new(
         pid: LocalPid,
         manager: Manager,
-        adapter: Adapter,
-        event_sender: mpsc::Sender<CentralEvent>,
-        event_receiver: Arc<RwLock<mpsc::Receiver<CentralEvent>>>,
+        adapters: Vec<CentralAdapter>,
+        event_sender: mpsc::Sender<AdapterEvent>,
+        event_receiver: Arc<RwLock<mpsc::Receiver<AdapterEvent>>>,
+        options: &CentralOptions,
     ) -> Self {
         CentralManagerState {
             pid,
             manager,
-            adapter,
+            adapter: adapters[0].adapter.clone(),
+            adapters: Arc::new(Mutex::new(adapters)),
+            adapter_rssi: Arc::new(Mutex::new(AdapterRssi::default())),
+            adapter_state: watch::Sender::new(AdapterStates::default()),
             event_sender,
             event_receiver,
             discovered_peripherals: Arc::new(Mutex::new(HashMap::new())),
+            scan_filter: Arc::new(Mutex::new(ScanFilter::default())),
+            advertisement_filter: Arc::new(Mutex::new(AdvertisementFilter::default())),
+            service_discovery: Arc::new(Mutex::new(options.service_discovery.clone())),
+            scan: Arc::new(Mutex::new(ScanState::default())),
+            update_throttle: Arc::new(Mutex::new(UpdateThrottle::new(
+                options.update_throttle.clone(),
+            ))),
+            scan_report: options
+                .scan_report_interval_ms
+                .map(|_| Arc::new(Mutex::new(ScanReport::default()))),
+            bthome_bindkeys: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
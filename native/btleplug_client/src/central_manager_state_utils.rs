@@ -1,4 +1,7 @@
 #![allow(unused_imports)]
+use crate::assigned_numbers::{
+    characteristic_name, company_name, descriptor_name, label, service_name,
+};
 use crate::central_manager_state::get_peripheral_rssi_cache;
 use crate::central_manager_state::{CentralRef, DISCOVERED_SERVICES, RSSI_CACHE};
 use crate::central_manager_utils::{
@@ -22,6 +25,7 @@ use btleplug::api::{CharPropFlags, PeripheralProperties};
 use std::iter::FromIterator;
 
 use btleplug::platform::Adapter;
+use uuid::Uuid;
 
 #[derive(NifStruct)]
 #[module = "RustlerBtleplug.AdapterInfo"]
@@ -39,6 +43,8 @@ struct PeripheralInfo {
     rssi_cache: Vec<(i64, i16)>,
     is_connected: bool,
     tx_power: Option<i16>,
+    /// Company names for the advertised manufacturer data
+    manufacturers: Vec<String>,
     services: Vec<ServiceInfo>, // **Nested directly inside Peripheral**
 }
 
@@ -47,6 +53,7 @@ struct PeripheralInfo {
 #[module = "RustlerBtleplug.ServiceInfo"]
 struct ServiceInfo {
     uuid: String,
+    name: Option<String>,
     characteristics: Vec<CharacteristicInfo>, // **Nested directly inside Service**
 }
 
@@ -55,7 +62,17 @@ struct ServiceInfo {
 #[module = "RustlerBtleplug.CharacteristicInfo"]
 struct CharacteristicInfo {
     uuid: String,
+    name: Option<String>,
     properties: Vec<String>,
+    descriptors: Vec<DescriptorInfo>,
+}
+
+/// ✅ **NifStruct for Descriptor** (e.g. 0x2901, 0x2902 CCCD, 0x2904)
+#[derive(NifStruct, Debug, Clone, PartialEq, Eq, Hash)]
+#[module = "RustlerBtleplug.DescriptorInfo"]
+struct DescriptorInfo {
+    uuid: String,
+    name: Option<String>,
 }
 
 /// ✅ **NifMap: Main Struct for Adapter State**
@@ -133,7 +150,16 @@ async fn adapter_state_to_map(adapter: &Adapter) -> AdapterState {
                 let char_props = get_characteristic_properties(char);
                 char_set.insert(CharacteristicInfo {
                     uuid: char.uuid.to_string(),
+                    name: characteristic_name(&char.uuid).map(String::from),
                     properties: char_props,
+                    descriptors: char
+                        .descriptors
+                        .iter()
+                        .map(|d| DescriptorInfo {
+                            uuid: d.uuid.to_string(),
+                            name: descriptor_name(&d.uuid).map(String::from),
+                        })
+                        .collect(),
                 });
             }
         }
@@ -142,11 +168,16 @@ async fn adapter_state_to_map(adapter: &Adapter) -> AdapterState {
         let service_infos: Vec<ServiceInfo> = service_map
             .into_iter()
             .map(|(uuid, characteristics)| ServiceInfo {
+                name: Uuid::parse_str(&uuid)
+                    .ok()
+                    .and_then(|uuid| service_name(&uuid))
+                    .map(String::from),
                 uuid,
                 characteristics: characteristics.into_iter().collect(),
             })
             .collect();
 
+        let manufacturers = manufacturer_names(&properties);
         let peripheral_info = PeripheralInfo {
             id: peripheral_id.clone(),
             name: properties.local_name.unwrap_or(peripheral_id.clone()),
@@ -154,6 +185,7 @@ async fn adapter_state_to_map(adapter: &Adapter) -> AdapterState {
             rssi_cache,
             is_connected,
             tx_power: properties.tx_power_level,
+            manufacturers,
             services: service_infos,
         };
 
@@ -166,6 +198,20 @@ async fn adapter_state_to_map(adapter: &Adapter) -> AdapterState {
     }
 }
 
+/// 🏭 **Company names** for the manufacturer data keys, `"0x1234"` when unknown
+fn manufacturer_names(properties: &PeripheralProperties) -> Vec<String> {
+    let mut company_ids: Vec<u16> = properties.manufacturer_data.keys().copied().collect();
+    company_ids.sort_unstable();
+    company_ids
+        .into_iter()
+        .map(|id| {
+            company_name(id)
+                .map(String::from)
+                .unwrap_or_else(|| format!("0x{:04X}", id))
+        })
+        .collect()
+}
+
 #[rustler::nif]
 pub fn get_adapter_state_graph(
     env: Env<'_>,
@@ -253,7 +299,8 @@ pub async fn adapter_state_to_mermaid_mindmap(adapter: &Adapter) -> String {
             let service_id = service.uuid.to_string();
             output.push_str(&format!(
                 "            {}[Service: {}]\n",
-                service_id, service_id
+                service_id,
+                label(service_name(&service.uuid), &service_id)
             ));
 
             // 4️⃣ Fetch Characteristics
@@ -292,7 +339,9 @@ pub async fn adapter_state_to_mermaid_mindmap(adapter: &Adapter) -> String {
                 );
                 output.push_str(&format!(
                     "                {}[Characteristic: {} {}]\n",
-                    char_id, char_id, char_flags
+                    char_id,
+                    label(characteristic_name(&char.uuid), &char_id),
+                    char_flags
                 ));
             }
         }
@@ -345,7 +394,9 @@ pub async fn adapter_state_to_mermaid_graph(adapter: &Adapter) -> String {
             let service_id = service.uuid.to_string();
             output.push_str(&format!(
                 "    {} --> {}[\"Service: {}\"]\n",
-                peripheral_id, service_id, service_id
+                peripheral_id,
+                service_id,
+                label(service_name(&service.uuid), &service_id)
             ));
 
             // 4️⃣ Fetch Characteristics
@@ -385,7 +436,10 @@ pub async fn adapter_state_to_mermaid_graph(adapter: &Adapter) -> String {
 
                 output.push_str(&format!(
                     "    {} --> {}[\"Characteristic: {} {}\"]\n",
-                    service_id, char_id, char_id, char_flags
+                    service_id,
+                    char_id,
+                    label(characteristic_name(&char.uuid), &char_id),
+                    char_flags
                 ));
             }
         }
@@ -14,7 +14,7 @@ use std::sync::Arc;
 
 use serde_json::{Map, Value};
 
-use btleplug::api::{CharPropFlags, PeripheralProperties};
+use btleplug::api::{CharPropFlags, PeripheralProperties, ScanFilter};
 use std::iter::FromIterator;
 
 use btleplug::platform::Adapter;
@@ -35,6 +35,14 @@ pub async fn get_peripheral_properties(
     None
 }
 
+/// 🚦 **Does an advertisement match the service UUIDs of the active scan filter?**
+pub fn matches_scan_filter(filter: &ScanFilter, properties: &PeripheralProperties) -> bool {
+    filter.services.is_empty()
+        || filter.services.iter().any(|service| {
+            properties.services.contains(service) || properties.service_data.contains_key(service)
+        })
+}
+
 pub fn get_characteristic_properties(characteristic: &Characteristic) -> Vec<String> {
     let mut properties = Vec::new();
 
@@ -179,3 +187,77 @@ pub fn properties_to_map<'a>(env: Env<'a>, props: &PeripheralProperties) -> Term
 
     map.encode(env)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use btleplug::api::bleuuid::uuid_from_u16;
+
+    const HEART_RATE: u16 = 0x180D;
+    const BATTERY: u16 = 0x180F;
+
+    fn filter(services: &[u16]) -> ScanFilter {
+        ScanFilter {
+            services: services.iter().map(|uuid| uuid_from_u16(*uuid)).collect(),
+        }
+    }
+
+    fn advertising(service: u16) -> PeripheralProperties {
+        PeripheralProperties {
+            services: vec![uuid_from_u16(service)],
+            ..PeripheralProperties::default()
+        }
+    }
+
+    fn with_service_data(service: u16) -> PeripheralProperties {
+        PeripheralProperties {
+            service_data: HashMap::from([(uuid_from_u16(service), vec![87])]),
+            ..PeripheralProperties::default()
+        }
+    }
+
+    #[test]
+    fn empty_filter_reports_everything() {
+        assert!(matches_scan_filter(&filter(&[]), &advertising(HEART_RATE)));
+        assert!(matches_scan_filter(
+            &filter(&[]),
+            &PeripheralProperties::default()
+        ));
+    }
+
+    #[test]
+    fn matches_advertised_services() {
+        assert!(matches_scan_filter(
+            &filter(&[HEART_RATE]),
+            &advertising(HEART_RATE)
+        ));
+        assert!(matches_scan_filter(
+            &filter(&[BATTERY, HEART_RATE]),
+            &advertising(HEART_RATE)
+        ));
+        assert!(!matches_scan_filter(
+            &filter(&[BATTERY]),
+            &advertising(HEART_RATE)
+        ));
+    }
+
+    #[test]
+    fn matches_service_data_uuids() {
+        assert!(matches_scan_filter(
+            &filter(&[BATTERY]),
+            &with_service_data(BATTERY)
+        ));
+        assert!(!matches_scan_filter(
+            &filter(&[HEART_RATE]),
+            &with_service_data(BATTERY)
+        ));
+    }
+
+    #[test]
+    fn filtered_scan_drops_advertisements_without_services() {
+        assert!(!matches_scan_filter(
+            &filter(&[HEART_RATE]),
+            &PeripheralProperties::default()
+        ));
+    }
+}
@@ -0,0 +1,490 @@
+use crate::atoms;
+use crate::central_manager_options::parse_uuid;
+use crate::peripheral_utils::atom_map;
+
+use log::debug;
+use rustler::{Atom, Binary, Encoder, Env, NifResult, Term};
+
+use crate::assigned_numbers::short_uuid;
+use uuid::Uuid;
+
+const BLOOD_PRESSURE_MEASUREMENT: u16 = 0x2A35;
+const INTERMEDIATE_CUFF_PRESSURE: u16 = 0x2A36;
+const HEART_RATE_MEASUREMENT: u16 = 0x2A37;
+const BATTERY_LEVEL: u16 = 0x2A19;
+const TEMPERATURE_MEASUREMENT: u16 = 0x2A1C;
+const INTERMEDIATE_TEMPERATURE: u16 = 0x2A1E;
+const RSC_MEASUREMENT: u16 = 0x2A53;
+const CSC_MEASUREMENT: u16 = 0x2A5B;
+const WEIGHT_MEASUREMENT: u16 = 0x2A9D;
+
+/// 📅 **Date Time** characteristic layout, encodes as `{{y, m, d}, {h, min, s}}`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hours: u8,
+    minutes: u8,
+    seconds: u8,
+}
+
+impl Encoder for DateTime {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        (
+            (self.year, self.month, self.day),
+            (self.hours, self.minutes, self.seconds),
+        )
+            .encode(env)
+    }
+}
+
+/// 🩺 **Decoded value of a standard characteristic**
+#[derive(Debug, Clone, PartialEq)]
+pub enum GattValue {
+    BatteryLevel {
+        /// %
+        level: u8,
+    },
+    HeartRate {
+        bpm: u16,
+        /// `None` when the sensor can't detect skin contact
+        sensor_contact: Option<bool>,
+        energy_expended_kj: Option<u16>,
+        rr_intervals_ms: Vec<f64>,
+    },
+    Temperature {
+        temperature: Option<f64>,
+        unit: &'static str,
+        timestamp: Option<DateTime>,
+        temperature_type: Option<Atom>,
+    },
+    CyclingSpeedCadence {
+        cumulative_wheel_revolutions: Option<u32>,
+        /// 1/1024 s, wraps around
+        last_wheel_event_time: Option<u16>,
+        cumulative_crank_revolutions: Option<u16>,
+        /// 1/1024 s, wraps around
+        last_crank_event_time: Option<u16>,
+    },
+    RunningSpeedCadence {
+        speed_mps: f64,
+        cadence: u8,
+        stride_length_m: Option<f64>,
+        total_distance_m: Option<f64>,
+        running: bool,
+    },
+    Weight {
+        /// `None` when the measurement was unsuccessful
+        weight: Option<f64>,
+        unit: &'static str,
+        timestamp: Option<DateTime>,
+        user_id: Option<u8>,
+        bmi: Option<f64>,
+        height: Option<f64>,
+        height_unit: &'static str,
+    },
+    BloodPressure {
+        systolic: Option<f64>,
+        diastolic: Option<f64>,
+        mean_arterial_pressure: Option<f64>,
+        unit: &'static str,
+        timestamp: Option<DateTime>,
+        pulse_rate: Option<f64>,
+        user_id: Option<u8>,
+        measurement_status: Option<u16>,
+    },
+}
+
+/// Little-endian cursor over a characteristic value
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn sfloat(&mut self) -> Option<Option<f64>> {
+        self.u16().map(sfloat)
+    }
+
+    fn float(&mut self) -> Option<Option<f64>> {
+        self.u32().map(float)
+    }
+
+    fn date_time(&mut self) -> Option<DateTime> {
+        Some(DateTime {
+            year: self.u16()?,
+            month: self.u8()?,
+            day: self.u8()?,
+            hours: self.u8()?,
+            minutes: self.u8()?,
+            seconds: self.u8()?,
+        })
+    }
+
+    fn optional<T>(
+        &mut self,
+        present: bool,
+        read: impl FnOnce(&mut Self) -> Option<T>,
+    ) -> Option<Option<T>> {
+        if present {
+            read(self).map(Some)
+        } else {
+            Some(None)
+        }
+    }
+}
+
+fn flag(flags: u8, bit: u8) -> bool {
+    flags & (1 << bit) != 0
+}
+
+/// mantissa × 10^exponent, dividing for negative exponents to keep `36.5` exact
+fn scale(mantissa: i32, exponent: i32) -> f64 {
+    if exponent < 0 {
+        mantissa as f64 / 10f64.powi(-exponent)
+    } else {
+        mantissa as f64 * 10f64.powi(exponent)
+    }
+}
+
+/// IEEE-11073 16-bit SFLOAT, `None` for NaN, NRes, ±INF and reserved values
+pub fn sfloat(raw: u16) -> Option<f64> {
+    if (0x07FE..=0x0802).contains(&raw) {
+        return None;
+    }
+    let mantissa = ((raw << 4) as i16 >> 4) as i32;
+    let exponent = (raw as i16 >> 12) as i32;
+    Some(scale(mantissa, exponent))
+}
+
+/// IEEE-11073 32-bit FLOAT, `None` for NaN, NRes, ±INF and reserved values
+pub fn float(raw: u32) -> Option<f64> {
+    if (0x007F_FFFE..=0x0080_0002).contains(&raw) {
+        return None;
+    }
+    let mantissa = ((raw << 8) as i32) >> 8;
+    let exponent = (raw as i32) >> 24;
+    Some(scale(mantissa, exponent))
+}
+
+fn temperature_type(value: u8) -> Option<Atom> {
+    match value {
+        1 => Some(atoms::armpit()),
+        2 => Some(atoms::body()),
+        3 => Some(atoms::ear()),
+        4 => Some(atoms::finger()),
+        5 => Some(atoms::gastrointestinal()),
+        6 => Some(atoms::mouth()),
+        7 => Some(atoms::rectum()),
+        8 => Some(atoms::toe()),
+        9 => Some(atoms::tympanum()),
+        _ => None,
+    }
+}
+
+impl GattValue {
+    /// 🔍 **Decode a value of a known characteristic**, `None` for unknown UUIDs
+    /// or truncated payloads
+    pub fn decode(characteristic_uuid: &Uuid, data: &[u8]) -> Option<GattValue> {
+        let mut reader = Reader { data };
+        let decoded = match short_uuid(characteristic_uuid)? {
+            BATTERY_LEVEL => Self::battery_level(&mut reader),
+            HEART_RATE_MEASUREMENT => Self::heart_rate(&mut reader),
+            TEMPERATURE_MEASUREMENT | INTERMEDIATE_TEMPERATURE => Self::temperature(&mut reader),
+            CSC_MEASUREMENT => Self::csc(&mut reader),
+            RSC_MEASUREMENT => Self::rsc(&mut reader),
+            WEIGHT_MEASUREMENT => Self::weight(&mut reader),
+            BLOOD_PRESSURE_MEASUREMENT | INTERMEDIATE_CUFF_PRESSURE => {
+                Self::blood_pressure(&mut reader)
+            }
+            _ => return None,
+        };
+
+        if decoded.is_none() {
+            debug!(
+                "⚠️ Could not decode {} value: {:02X?}",
+                characteristic_uuid, data
+            );
+        }
+        decoded
+    }
+
+    fn battery_level(reader: &mut Reader) -> Option<GattValue> {
+        Some(GattValue::BatteryLevel {
+            level: reader.u8()?,
+        })
+    }
+
+    fn heart_rate(reader: &mut Reader) -> Option<GattValue> {
+        let flags = reader.u8()?;
+        let bpm = if flag(flags, 0) {
+            reader.u16()?
+        } else {
+            reader.u8()? as u16
+        };
+        let energy_expended_kj = reader.optional(flag(flags, 3), Reader::u16)?;
+
+        let mut rr_intervals_ms = Vec::new();
+        if flag(flags, 4) {
+            while let Some(rr) = reader.u16() {
+                rr_intervals_ms.push(rr as f64 * 1000.0 / 1024.0);
+            }
+        }
+
+        Some(GattValue::HeartRate {
+            bpm,
+            sensor_contact: flag(flags, 2).then_some(flag(flags, 1)),
+            energy_expended_kj,
+            rr_intervals_ms,
+        })
+    }
+
+    fn temperature(reader: &mut Reader) -> Option<GattValue> {
+        let flags = reader.u8()?;
+        let temperature = reader.float()?;
+        let timestamp = reader.optional(flag(flags, 1), Reader::date_time)?;
+        let temperature_type = reader.optional(flag(flags, 2), Reader::u8)?;
+
+        Some(GattValue::Temperature {
+            temperature,
+            unit: if flag(flags, 0) { "°F" } else { "°C" },
+            timestamp,
+            temperature_type: temperature_type.and_then(self::temperature_type),
+        })
+    }
+
+    fn csc(reader: &mut Reader) -> Option<GattValue> {
+        let flags = reader.u8()?;
+        let wheel = reader.optional(flag(flags, 0), |r| Some((r.u32()?, r.u16()?)))?;
+        let crank = reader.optional(flag(flags, 1), |r| Some((r.u16()?, r.u16()?)))?;
+
+        Some(GattValue::CyclingSpeedCadence {
+            cumulative_wheel_revolutions: wheel.map(|(revolutions, _)| revolutions),
+            last_wheel_event_time: wheel.map(|(_, time)| time),
+            cumulative_crank_revolutions: crank.map(|(revolutions, _)| revolutions),
+            last_crank_event_time: crank.map(|(_, time)| time),
+        })
+    }
+
+    fn rsc(reader: &mut Reader) -> Option<GattValue> {
+        let flags = reader.u8()?;
+        let speed = reader.u16()?;
+        let cadence = reader.u8()?;
+        let stride_length = reader.optional(flag(flags, 0), Reader::u16)?;
+        let total_distance = reader.optional(flag(flags, 1), Reader::u32)?;
+
+        Some(GattValue::RunningSpeedCadence {
+            speed_mps: speed as f64 / 256.0,
+            cadence,
+            stride_length_m: stride_length.map(|len| len as f64 / 100.0),
+            total_distance_m: total_distance.map(|distance| distance as f64 / 10.0),
+            running: flag(flags, 2),
+        })
+    }
+
+    fn weight(reader: &mut Reader) -> Option<GattValue> {
+        let flags = reader.u8()?;
+        let imperial = flag(flags, 0);
+        let weight = reader.u16()?;
+        let timestamp = reader.optional(flag(flags, 1), Reader::date_time)?;
+        let user_id = reader.optional(flag(flags, 2), Reader::u8)?;
+        let bmi_height = reader.optional(flag(flags, 3), |r| Some((r.u16()?, r.u16()?)))?;
+
+        let (weight_resolution, height_resolution) = if imperial {
+            (0.01, 0.1)
+        } else {
+            (0.005, 0.001)
+        };
+
+        Some(GattValue::Weight {
+            weight: (weight != 0xFFFF).then(|| round(weight as f64 * weight_resolution, 3)),
+            unit: if imperial { "lb" } else { "kg" },
+            timestamp,
+            user_id,
+            bmi: bmi_height.map(|(bmi, _)| round(bmi as f64 * 0.1, 1)),
+            height: bmi_height.map(|(_, height)| round(height as f64 * height_resolution, 3)),
+            height_unit: if imperial { "in" } else { "m" },
+        })
+    }
+
+    fn blood_pressure(reader: &mut Reader) -> Option<GattValue> {
+        let flags = reader.u8()?;
+        let systolic = reader.sfloat()?;
+        let diastolic = reader.sfloat()?;
+        let mean_arterial_pressure = reader.sfloat()?;
+        let timestamp = reader.optional(flag(flags, 1), Reader::date_time)?;
+        let pulse_rate = reader.optional(flag(flags, 2), Reader::sfloat)?.flatten();
+        let user_id = reader.optional(flag(flags, 3), Reader::u8)?;
+        let measurement_status = reader.optional(flag(flags, 4), Reader::u16)?;
+
+        Some(GattValue::BloodPressure {
+            systolic,
+            diastolic,
+            mean_arterial_pressure,
+            unit: if flag(flags, 0) { "kPa" } else { "mmHg" },
+            timestamp,
+            pulse_rate,
+            user_id,
+            measurement_status,
+        })
+    }
+
+    fn fields<'a>(&self, env: Env<'a>) -> Vec<(Atom, Term<'a>)> {
+        match self {
+            GattValue::BatteryLevel { level } => vec![
+                (atoms::type_(), atoms::battery_level().encode(env)),
+                (atoms::battery_level(), level.encode(env)),
+            ],
+            GattValue::HeartRate {
+                bpm,
+                sensor_contact,
+                energy_expended_kj,
+                rr_intervals_ms,
+            } => vec![
+                (atoms::type_(), atoms::heart_rate_measurement().encode(env)),
+                (atoms::heart_rate(), bpm.encode(env)),
+                (atoms::sensor_contact(), sensor_contact.encode(env)),
+                (atoms::energy_expended_kj(), energy_expended_kj.encode(env)),
+                (atoms::rr_intervals_ms(), rr_intervals_ms.encode(env)),
+            ],
+            GattValue::Temperature {
+                temperature,
+                unit,
+                timestamp,
+                temperature_type,
+            } => vec![
+                (atoms::type_(), atoms::temperature_measurement().encode(env)),
+                (atoms::temperature(), temperature.encode(env)),
+                (atoms::unit(), unit.encode(env)),
+                (atoms::timestamp(), timestamp.encode(env)),
+                (atoms::temperature_type(), temperature_type.encode(env)),
+            ],
+            GattValue::CyclingSpeedCadence {
+                cumulative_wheel_revolutions,
+                last_wheel_event_time,
+                cumulative_crank_revolutions,
+                last_crank_event_time,
+            } => vec![
+                (atoms::type_(), atoms::csc_measurement().encode(env)),
+                (
+                    atoms::cumulative_wheel_revolutions(),
+                    cumulative_wheel_revolutions.encode(env),
+                ),
+                (
+                    atoms::last_wheel_event_time(),
+                    last_wheel_event_time.encode(env),
+                ),
+                (
+                    atoms::cumulative_crank_revolutions(),
+                    cumulative_crank_revolutions.encode(env),
+                ),
+                (
+                    atoms::last_crank_event_time(),
+                    last_crank_event_time.encode(env),
+                ),
+            ],
+            GattValue::RunningSpeedCadence {
+                speed_mps,
+                cadence,
+                stride_length_m,
+                total_distance_m,
+                running,
+            } => vec![
+                (atoms::type_(), atoms::rsc_measurement().encode(env)),
+                (atoms::speed_mps(), speed_mps.encode(env)),
+                (atoms::cadence(), cadence.encode(env)),
+                (atoms::stride_length_m(), stride_length_m.encode(env)),
+                (atoms::total_distance_m(), total_distance_m.encode(env)),
+                (atoms::running(), running.encode(env)),
+            ],
+            GattValue::Weight {
+                weight,
+                unit,
+                timestamp,
+                user_id,
+                bmi,
+                height,
+                height_unit,
+            } => vec![
+                (atoms::type_(), atoms::weight_measurement().encode(env)),
+                (atoms::weight(), weight.encode(env)),
+                (atoms::unit(), unit.encode(env)),
+                (atoms::timestamp(), timestamp.encode(env)),
+                (atoms::user_id(), user_id.encode(env)),
+                (atoms::bmi(), bmi.encode(env)),
+                (atoms::height(), height.encode(env)),
+                (atoms::height_unit(), height_unit.encode(env)),
+            ],
+            GattValue::BloodPressure {
+                systolic,
+                diastolic,
+                mean_arterial_pressure,
+                unit,
+                timestamp,
+                pulse_rate,
+                user_id,
+                measurement_status,
+            } => vec![
+                (
+                    atoms::type_(),
+                    atoms::blood_pressure_measurement().encode(env),
+                ),
+                (atoms::systolic(), systolic.encode(env)),
+                (atoms::diastolic(), diastolic.encode(env)),
+                (
+                    atoms::mean_arterial_pressure(),
+                    mean_arterial_pressure.encode(env),
+                ),
+                (atoms::unit(), unit.encode(env)),
+                (atoms::timestamp(), timestamp.encode(env)),
+                (atoms::pulse_rate(), pulse_rate.encode(env)),
+                (atoms::user_id(), user_id.encode(env)),
+                (atoms::measurement_status(), measurement_status.encode(env)),
+            ],
+        }
+    }
+}
+
+/// Encodes as `%{type: :heart_rate_measurement | :battery_level | ..., ...fields}`
+impl Encoder for GattValue {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        atom_map(env, self.fields(env))
+    }
+}
+
+fn round(value: f64, digits: i32) -> f64 {
+    let factor = 10f64.powi(digits);
+    (value * factor).round() / factor
+}
+
+/// 🔍 **Decode a single characteristic value**, `nil` for unknown characteristics
+#[rustler::nif]
+pub fn decode_characteristic_value(
+    characteristic_uuid: String,
+    data: Binary,
+) -> NifResult<Option<GattValue>> {
+    let uuid = parse_uuid(&characteristic_uuid)?;
+    Ok(GattValue::decode(&uuid, data.as_slice()))
+}
@@ -27,14 +27,29 @@ use mimalloc::MiMalloc;
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+mod advertisement;
+mod advertisement_filter;
+mod advertisement_throttle;
+mod assigned_numbers;
 mod atoms;
+mod beacon;
+mod bthome;
 mod central_manager;
+mod central_manager_adapters;
 mod central_manager_finder;
+mod central_manager_options;
+mod central_manager_recovery;
 mod central_manager_state;
 mod central_manager_state_utils;
 mod central_manager_utils;
+mod gatt_values;
 mod logging;
+mod payload_schema;
 mod peripheral;
+mod peripheral_subscriptions;
+mod peripheral_utils;
+mod presentation_format;
+mod scan_report;
 
 extern crate rustler;
 extern crate rustler_codegen;
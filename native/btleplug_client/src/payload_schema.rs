@@ -0,0 +1,504 @@
+use crate::atoms;
+use crate::peripheral_utils::{atom_map, BinaryValue};
+
+use log::debug;
+use rustler::types::map::MapIterator;
+use rustler::types::tuple::get_tuple;
+use rustler::{Atom, Decoder, Encoder, Env, Error as RustlerError, NifResult, Term, TermType};
+
+use std::collections::HashMap;
+
+/// Longest `{:string, n}` / `{:bytes, n}` field, well above any ATT value
+const MAX_FIELD_LENGTH: usize = 512;
+
+/// 🧱 **Wire type of a schema field**
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldType {
+    Int {
+        bytes: usize,
+        signed: bool,
+        big_endian: bool,
+    },
+    Float {
+        bytes: usize,
+        big_endian: bool,
+    },
+    /// Flag names for bits 0..n, packed into as many bytes as needed
+    Bitfield(Vec<String>),
+    /// Fixed length, NUL padded UTF-8
+    String(usize),
+    Bytes(usize),
+}
+
+impl FieldType {
+    /// `u8`, `i8`, `u16le`, `i32be`, `f32le`, ... (`le` / `be` is required above 8 bits)
+    fn parse_number(name: &str) -> Option<FieldType> {
+        let kind = name.get(..1)?;
+        let rest = &name[1..];
+        let (bits, big_endian) = match rest {
+            "8" => ("8", false),
+            _ if rest.ends_with("le") => (&rest[..rest.len() - 2], false),
+            _ if rest.ends_with("be") => (&rest[..rest.len() - 2], true),
+            _ => return None,
+        };
+        let bytes = match bits {
+            "8" => 1,
+            "16" => 2,
+            "32" => 4,
+            "64" => 8,
+            _ => return None,
+        };
+
+        match kind {
+            "u" | "i" => Some(FieldType::Int {
+                bytes,
+                signed: kind == "i",
+                big_endian,
+            }),
+            "f" if bytes >= 4 => Some(FieldType::Float { bytes, big_endian }),
+            _ => None,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            FieldType::Int { bytes, .. } | FieldType::Float { bytes, .. } => *bytes,
+            FieldType::Bitfield(flags) => flags.len().div_ceil(8),
+            FieldType::String(len) | FieldType::Bytes(len) => *len,
+        }
+    }
+}
+
+impl<'a> Decoder<'a> for FieldType {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if let Ok(name) = term.atom_to_string() {
+            return FieldType::parse_number(&name).ok_or_else(|| invalid_schema(name));
+        }
+
+        let (kind, arg): (Atom, Term) = term.decode()?;
+        if kind == atoms::bitfield() {
+            let flags: Vec<Term> = arg.decode()?;
+            if flags.len() > 64 {
+                return Err(invalid_schema("bitfield wider than 64 bits".to_string()));
+            }
+            flags
+                .iter()
+                .map(|flag| flag.atom_to_string())
+                .collect::<NifResult<_>>()
+                .map(FieldType::Bitfield)
+        } else if kind == atoms::string() {
+            Ok(FieldType::String(decode_length(arg)?))
+        } else if kind == atoms::bytes() {
+            Ok(FieldType::Bytes(decode_length(arg)?))
+        } else {
+            Err(invalid_schema(format!("{:?}", kind)))
+        }
+    }
+}
+
+fn decode_length(term: Term) -> NifResult<usize> {
+    let len: usize = term.decode()?;
+    if len > MAX_FIELD_LENGTH {
+        return Err(invalid_schema(format!(
+            "field length {} above {}",
+            len, MAX_FIELD_LENGTH
+        )));
+    }
+    Ok(len)
+}
+
+/// 📐 **One field**: `{name, type}` or `{name, type, scale: 0.01}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaField {
+    pub name: String,
+    pub kind: FieldType,
+    pub scale: Option<f64>,
+}
+
+impl<'a> Decoder<'a> for SchemaField {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let (name, kind, options): (Atom, FieldType, Vec<(Atom, Term)>) =
+            match get_tuple(term)?.as_slice() {
+                [name, kind] => (name.decode()?, kind.decode()?, Vec::new()),
+                [name, kind, options] => (name.decode()?, kind.decode()?, options.decode()?),
+                _ => return Err(invalid_schema(format!("{:?}", term))),
+            };
+
+        let mut scale = None;
+        for (key, value) in options {
+            if key != atoms::scale() {
+                return Err(invalid_schema(format!("{:?}", key)));
+            }
+            let value = value
+                .decode::<f64>()
+                .or_else(|_| value.decode::<i64>().map(|scale| scale as f64))?;
+            if value == 0.0 {
+                return Err(invalid_schema("scale must not be 0".to_string()));
+            }
+            scale = Some(value);
+        }
+
+        Ok(SchemaField {
+            name: name.to_term(term.get_env()).atom_to_string()?,
+            kind,
+            scale,
+        })
+    }
+}
+
+/// 🧩 **Ordered list of packed fields** describing a characteristic payload
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadSchema {
+    pub fields: Vec<SchemaField>,
+}
+
+impl<'a> Decoder<'a> for PayloadSchema {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(PayloadSchema {
+            fields: term.decode()?,
+        })
+    }
+}
+
+fn invalid_schema(reason: String) -> RustlerError {
+    RustlerError::Term(Box::new((atoms::invalid_schema(), reason)))
+}
+
+/// ❌ **Why a map could not be packed**
+#[derive(Debug, Clone, PartialEq)]
+pub enum PayloadError {
+    MissingField(String),
+    InvalidField(String),
+}
+
+impl Encoder for PayloadError {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let (reason, field) = match self {
+            PayloadError::MissingField(field) => (atoms::missing_field(), field),
+            PayloadError::InvalidField(field) => (atoms::invalid_field(), field),
+        };
+        (reason, field_atom(env, field)).encode(env)
+    }
+}
+
+fn field_atom<'a>(env: Env<'a>, name: &str) -> Term<'a> {
+    Atom::from_str(env, name)
+        .map(|atom| atom.encode(env))
+        .unwrap_or_else(|_| name.encode(env))
+}
+
+/// 📦 **Decoded field value**
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Int(i128),
+    Float(f64),
+    Flags(Vec<(String, bool)>),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl Encoder for FieldValue {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            FieldValue::Int(value) => match i64::try_from(*value) {
+                Ok(value) => value.encode(env),
+                Err(_) => (*value as u64).encode(env),
+            },
+            FieldValue::Float(value) => value.encode(env),
+            FieldValue::Flags(flags) => atom_map(
+                env,
+                flags
+                    .iter()
+                    .filter_map(|(name, set)| {
+                        Some((Atom::from_str(env, name).ok()?, set.encode(env)))
+                    })
+                    .collect(),
+            ),
+            FieldValue::String(value) => value.encode(env),
+            FieldValue::Bytes(value) => BinaryValue(value.clone()).encode(env),
+        }
+    }
+}
+
+/// Encodes as `%{field_name => value}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedPayload(pub Vec<(String, FieldValue)>);
+
+impl Encoder for DecodedPayload {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        atom_map(
+            env,
+            self.0
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((Atom::from_str(env, name).ok()?, value.encode(env)))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// ✍️ **Field values of a map passed to `write`**, packed once the characteristic is known
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldInput {
+    Number(f64),
+    Int(i128),
+    Bytes(Vec<u8>),
+    Flags(HashMap<String, bool>),
+}
+
+impl<'a> Decoder<'a> for FieldInput {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        match term.get_type() {
+            TermType::Integer => Ok(FieldInput::Int(
+                term.decode::<i64>()
+                    .map(i128::from)
+                    .or_else(|_| term.decode::<u64>().map(i128::from))?,
+            )),
+            TermType::Float => Ok(FieldInput::Number(term.decode()?)),
+            TermType::Binary => Ok(FieldInput::Bytes(BinaryValue::decode(term)?.0)),
+            TermType::List => {
+                let flags: Vec<Term> = term.decode()?;
+                flags
+                    .iter()
+                    .map(|flag| Ok((flag.atom_to_string()?, true)))
+                    .collect::<NifResult<_>>()
+                    .map(FieldInput::Flags)
+            }
+            TermType::Map => MapIterator::new(term)
+                .ok_or(RustlerError::BadArg)?
+                .map(|(key, value)| Ok((key.atom_to_string()?, value.decode::<bool>()?)))
+                .collect::<NifResult<_>>()
+                .map(FieldInput::Flags),
+            _ => Err(RustlerError::BadArg),
+        }
+    }
+}
+
+/// 📤 **Write payload**: raw bytes, or a map packed with the registered schema
+#[derive(Debug, Clone, PartialEq)]
+pub enum WritePayload {
+    Raw(Vec<u8>),
+    Fields(HashMap<String, FieldInput>),
+}
+
+impl<'a> Decoder<'a> for WritePayload {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if let Ok(value) = BinaryValue::decode(term) {
+            return Ok(WritePayload::Raw(value.0));
+        }
+
+        decode_fields(term).map(WritePayload::Fields)
+    }
+}
+
+/// `%{field_name => value}` with atom keys
+fn decode_fields(term: Term) -> NifResult<HashMap<String, FieldInput>> {
+    MapIterator::new(term)
+        .ok_or(RustlerError::BadArg)?
+        .map(|(key, value)| Ok((key.atom_to_string()?, value.decode()?)))
+        .collect()
+}
+
+fn read_int(bytes: &[u8], signed: bool, big_endian: bool) -> i128 {
+    let mut buf = [0u8; 16];
+    let len = bytes.len();
+    if big_endian {
+        bytes
+            .iter()
+            .rev()
+            .enumerate()
+            .for_each(|(i, b)| buf[i] = *b);
+    } else {
+        buf[..len].copy_from_slice(bytes);
+    }
+    let raw = u128::from_le_bytes(buf);
+
+    if signed {
+        let shift = 128 - len * 8;
+        ((raw << shift) as i128) >> shift
+    } else {
+        raw as i128
+    }
+}
+
+fn write_int(value: i128, bytes: usize, big_endian: bool) -> Vec<u8> {
+    let mut out = value.to_le_bytes()[..bytes].to_vec();
+    if big_endian {
+        out.reverse();
+    }
+    out
+}
+
+fn int_range(bytes: usize, signed: bool) -> (i128, i128) {
+    let bits = bytes as u32 * 8;
+    if signed {
+        (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+    } else {
+        (0, (1i128 << bits) - 1)
+    }
+}
+
+impl PayloadSchema {
+    /// 📥 **Unpack a value**, `None` when it is shorter than the schema
+    pub fn decode(&self, data: &[u8]) -> Option<DecodedPayload> {
+        let mut offset = 0;
+        let mut fields = Vec::with_capacity(self.fields.len());
+
+        for field in &self.fields {
+            let size = field.kind.size();
+            let Some(bytes) = data.get(offset..offset + size) else {
+                debug!(
+                    "⚠️ Payload of {} bytes too short for field {}",
+                    data.len(),
+                    field.name
+                );
+                return None;
+            };
+            offset += size;
+
+            let value = match &field.kind {
+                FieldType::Int {
+                    signed, big_endian, ..
+                } => {
+                    let raw = read_int(bytes, *signed, *big_endian);
+                    match field.scale {
+                        Some(scale) => FieldValue::Float(raw as f64 * scale),
+                        None => FieldValue::Int(raw),
+                    }
+                }
+                FieldType::Float {
+                    bytes: 4,
+                    big_endian,
+                } => {
+                    let raw: [u8; 4] = bytes.try_into().ok()?;
+                    let value = if *big_endian {
+                        f32::from_be_bytes(raw)
+                    } else {
+                        f32::from_le_bytes(raw)
+                    } as f64;
+                    FieldValue::Float(value * field.scale.unwrap_or(1.0))
+                }
+                FieldType::Float { big_endian, .. } => {
+                    let raw: [u8; 8] = bytes.try_into().ok()?;
+                    let value = if *big_endian {
+                        f64::from_be_bytes(raw)
+                    } else {
+                        f64::from_le_bytes(raw)
+                    };
+                    FieldValue::Float(value * field.scale.unwrap_or(1.0))
+                }
+                FieldType::Bitfield(flags) => {
+                    let raw = read_int(bytes, false, false);
+                    FieldValue::Flags(
+                        flags
+                            .iter()
+                            .enumerate()
+                            .map(|(bit, name)| (name.clone(), raw & (1 << bit) != 0))
+                            .collect(),
+                    )
+                }
+                FieldType::String(_) => {
+                    let end = bytes.iter().position(|b| *b == 0).unwrap_or(size);
+                    FieldValue::String(String::from_utf8_lossy(&bytes[..end]).into_owned())
+                }
+                FieldType::Bytes(_) => FieldValue::Bytes(bytes.to_vec()),
+            };
+
+            fields.push((field.name.clone(), value));
+        }
+
+        Some(DecodedPayload(fields))
+    }
+
+    /// 📤 **Pack a map** in field order
+    pub fn encode(&self, values: &HashMap<String, FieldInput>) -> Result<Vec<u8>, PayloadError> {
+        let mut out = Vec::new();
+
+        for field in &self.fields {
+            let value = values
+                .get(&field.name)
+                .ok_or_else(|| PayloadError::MissingField(field.name.clone()))?;
+            let invalid = || PayloadError::InvalidField(field.name.clone());
+
+            match (&field.kind, value) {
+                (
+                    FieldType::Int {
+                        bytes,
+                        signed,
+                        big_endian,
+                    },
+                    FieldInput::Int(_) | FieldInput::Number(_),
+                ) => {
+                    let raw = match (value, field.scale) {
+                        (FieldInput::Int(value), None) => *value,
+                        (FieldInput::Int(value), Some(scale)) => {
+                            (*value as f64 / scale).round() as i128
+                        }
+                        (FieldInput::Number(value), scale) => {
+                            (value / scale.unwrap_or(1.0)).round() as i128
+                        }
+                        _ => return Err(invalid()),
+                    };
+                    let (min, max) = int_range(*bytes, *signed);
+                    if raw < min || raw > max {
+                        return Err(invalid());
+                    }
+                    out.extend(write_int(raw, *bytes, *big_endian));
+                }
+                (
+                    FieldType::Float { bytes, big_endian },
+                    FieldInput::Int(_) | FieldInput::Number(_),
+                ) => {
+                    let value = match value {
+                        FieldInput::Int(value) => *value as f64,
+                        FieldInput::Number(value) => *value,
+                        _ => return Err(invalid()),
+                    } / field.scale.unwrap_or(1.0);
+                    match (bytes, big_endian) {
+                        (4, false) => out.extend((value as f32).to_le_bytes()),
+                        (4, true) => out.extend((value as f32).to_be_bytes()),
+                        (_, false) => out.extend(value.to_le_bytes()),
+                        (_, true) => out.extend(value.to_be_bytes()),
+                    }
+                }
+                (FieldType::Bitfield(flags), FieldInput::Flags(set)) => {
+                    if set.keys().any(|name| !flags.contains(name)) {
+                        return Err(invalid());
+                    }
+                    let raw = flags
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, name)| set.get(*name).copied().unwrap_or(false))
+                        .fold(0i128, |raw, (bit, _)| raw | (1 << bit));
+                    out.extend(write_int(raw, field.kind.size(), false));
+                }
+                (FieldType::String(len), FieldInput::Bytes(bytes)) if bytes.len() <= *len => {
+                    out.extend(bytes);
+                    out.resize(out.len() + len - bytes.len(), 0);
+                }
+                (FieldType::Bytes(len), FieldInput::Bytes(bytes)) if bytes.len() == *len => {
+                    out.extend(bytes);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// 🔍 **Unpack a payload with an inline schema**, `nil` when it is too short
+#[rustler::nif]
+pub fn decode_payload(schema: PayloadSchema, data: BinaryValue) -> Option<DecodedPayload> {
+    schema.decode(&data.0)
+}
+
+/// 📤 **Pack a map with an inline schema**
+#[rustler::nif]
+pub fn encode_payload(schema: PayloadSchema, values: Term) -> Result<BinaryValue, RustlerError> {
+    schema
+        .encode(&decode_fields(values)?)
+        .map(BinaryValue)
+        .map_err(|e| RustlerError::Term(Box::new(e)))
+}
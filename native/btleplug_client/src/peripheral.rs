@@ -1,16 +1,39 @@
 #![allow(unused_mut)]
 
 use crate::atoms;
+use crate::central_manager_adapters::AdapterEvent;
+use crate::central_manager_options::parse_uuid;
+use crate::gatt_values::GattValue;
+use crate::payload_schema::{DecodedPayload, PayloadSchema, WritePayload};
+use crate::peripheral_subscriptions::{Route, Subscriptions};
+use crate::peripheral_utils::{
+    decode_write_type, ensure_services_discovered, ensure_writable, find_characteristic,
+    find_descriptor, monotonic_time_us, system_time_us, BinaryValue, CharacteristicAddress,
+    GattError, OperationReply,
+};
+use crate::presentation_format::{FormattedValue, PresentationFormat};
 use crate::RUNTIME;
 use log::{debug, info, warn};
 
-use btleplug::api::{CentralEvent, CharPropFlags, Peripheral as ApiPeripheral};
+use btleplug::api::bleuuid::uuid_from_u16;
+use btleplug::api::{
+    CentralEvent, CharPropFlags, Characteristic, Peripheral as ApiPeripheral, ValueNotification,
+};
 use btleplug::platform::Peripheral;
-use futures::StreamExt;
-use rustler::{Encoder, Env, Error as RustlerError, LocalPid, OwnedEnv, ResourceArc};
-use std::sync::{Arc, Mutex};
+use futures::{Stream, StreamExt};
+use rustler::{
+    Atom, Binary, Encoder, Env, Error as RustlerError, LocalPid, NifMap, OwnedEnv, Reference,
+    ResourceArc, Term,
+};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
 use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
 use tokio::time::{timeout, Duration};
+use uuid::Uuid;
+
+const PRESENTATION_FORMAT_DESCRIPTOR: u16 = 0x2904;
 
 pub struct PeripheralRef(pub(crate) Arc<Mutex<PeripheralState>>);
 
@@ -28,14 +51,22 @@ pub struct PeripheralState {
     pub pid: LocalPid,
     pub peripheral: Arc<Peripheral>,
     pub state: PeripheralStateEnum,
-    pub event_receiver: Arc<RwLock<mpsc::Receiver<CentralEvent>>>,
+    pub event_receiver: Arc<RwLock<mpsc::Receiver<AdapterEvent>>>,
+    pub subscriptions: Subscriptions,
+    pub notification_task: Option<JoinHandle<()>>,
+    /// Attach decoded values of standard characteristics to reads and notifications
+    pub decode_values: bool,
+    /// Registered payload layouts by (service, characteristic) UUID
+    pub payload_schemas: HashMap<(Uuid, Uuid), Arc<PayloadSchema>>,
+    /// 0x2904 descriptors read during service discovery
+    pub presentation_formats: HashMap<(Uuid, Uuid), PresentationFormat>,
 }
 
 impl PeripheralState {
     pub fn new(
         pid: LocalPid,
         peripheral: Arc<Peripheral>,
-        event_receiver: Arc<RwLock<mpsc::Receiver<CentralEvent>>>,
+        event_receiver: Arc<RwLock<mpsc::Receiver<AdapterEvent>>>,
     ) -> Self {
         info!(
             "🔗 PeripheralState: new Peripheral: {:?} (Peripheral Ptr: {:p})",
@@ -48,6 +79,11 @@ impl PeripheralState {
             peripheral,
             state: PeripheralStateEnum::Disconnected,
             event_receiver,
+            subscriptions: Subscriptions::default(),
+            notification_task: None,
+            decode_values: false,
+            payload_schemas: HashMap::new(),
+            presentation_formats: HashMap::new(),
         }
     }
 
@@ -56,11 +92,313 @@ impl PeripheralState {
         debug!("🔄 State change: {:?} → {:?}", state_guard.state, new_state);
         state_guard.state = new_state;
     }
+
+    /// 📡 **Start the notification pump unless one is already running**
+    pub async fn ensure_notification_pump(
+        peripheral_arc: &Arc<Mutex<Self>>,
+        timeout_ms: u64,
+    ) -> Result<(), GattError> {
+        let (peripheral, pid) = {
+            let state_guard = peripheral_arc.lock().unwrap();
+            if state_guard
+                .notification_task
+                .as_ref()
+                .is_some_and(|task| !task.is_finished())
+            {
+                return Ok(());
+            }
+            (state_guard.peripheral.clone(), state_guard.pid)
+        };
+
+        let notifications = match timeout(
+            Duration::from_millis(timeout_ms),
+            peripheral.notifications(),
+        )
+        .await
+        {
+            Ok(Ok(notifications)) => notifications,
+            Ok(Err(e)) => {
+                warn!(
+                    "❌ Notification stream failed for {:?}: {:?}",
+                    peripheral.id(),
+                    e
+                );
+                return Err(GattError::Btleplug(e.to_string()));
+            }
+            Err(_) => {
+                warn!("⏳ Notification stream request timed out!");
+                return Err(GattError::Timeout);
+            }
+        };
+
+        let task = RUNTIME.spawn(run_notification_pump(
+            Arc::downgrade(peripheral_arc),
+            notifications,
+            pid,
+        ));
+
+        let mut state_guard = peripheral_arc.lock().unwrap();
+        if let Some(previous) = state_guard.notification_task.replace(task) {
+            previous.abort();
+        }
+        debug!("📡 Notification pump started for {:?}", peripheral.id());
+        Ok(())
+    }
+
+    /// 🔕 **Forget all subscriptions and stop the notification pump**
+    pub fn stop_notifications(peripheral_arc: &Arc<Mutex<Self>>) {
+        let mut state_guard = peripheral_arc.lock().unwrap();
+        state_guard.subscriptions.clear();
+        if let Some(task) = state_guard.notification_task.take() {
+            debug!(
+                "🔕 Stopping notification pump for {:?}",
+                state_guard.peripheral.id()
+            );
+            task.abort();
+        }
+    }
+
+    fn payload_schema(&self, characteristic: &Characteristic) -> Option<&Arc<PayloadSchema>> {
+        self.payload_schemas
+            .get(&(characteristic.service_uuid, characteristic.uuid))
+    }
+
+    /// 📏 **Presentation Format descriptor read for a characteristic during discovery**
+    fn presentation_format(&self, characteristic: &Characteristic) -> Option<&PresentationFormat> {
+        self.presentation_formats
+            .get(&(characteristic.service_uuid, characteristic.uuid))
+    }
+
+    /// 🧩 **Whether reads and notifications of a characteristic carry a decoded value**
+    pub fn decodes(&self, characteristic: &Characteristic) -> bool {
+        self.decode_values
+            || self.payload_schema(characteristic).is_some()
+            || self.presentation_format(characteristic).is_some()
+    }
+
+    /// 🧩 **Registered payload schema first, then the standard decoders, then the
+    /// presentation format advertised by the device**
+    pub fn decode_value(
+        &self,
+        characteristic: &Characteristic,
+        value: &[u8],
+    ) -> Option<DecodedValue> {
+        if let Some(schema) = self.payload_schema(characteristic) {
+            return schema.decode(value).map(DecodedValue::Schema);
+        }
+
+        self.decode_standard(&characteristic.uuid, value)
+            .or_else(|| {
+                self.presentation_format(characteristic)?
+                    .apply(value)
+                    .map(DecodedValue::Formatted)
+            })
+    }
+
+    /// 🩺 **Standard decoders only**, all that applies when the service is unknown
+    fn decode_standard(&self, uuid: &Uuid, value: &[u8]) -> Option<DecodedValue> {
+        self.decode_values
+            .then(|| GattValue::decode(uuid, value))
+            .flatten()
+            .map(DecodedValue::Standard)
+    }
+
+    /// 🔕 **Drop a subscription, stopping the pump once nothing is subscribed**
+    pub fn remove_subscription(peripheral_arc: &Arc<Mutex<Self>>, characteristic: &Characteristic) {
+        let is_empty = {
+            let mut state_guard = peripheral_arc.lock().unwrap();
+            state_guard
+                .subscriptions
+                .remove(&characteristic.service_uuid, &characteristic.uuid);
+            state_guard.subscriptions.is_empty()
+        };
+
+        if is_empty {
+            Self::stop_notifications(peripheral_arc);
+        }
+    }
+}
+
+/// 📩 **Metadata sent along with every characteristic value change**
+#[derive(NifMap)]
+#[rustler(encode)]
+struct ValueChangedMeta {
+    peripheral_id: String,
+    /// `nil` when several subscribed services share the characteristic UUID
+    service_uuid: Option<String>,
+    monotonic_time: i64,
+    system_time: i64,
+    /// Set by a registered payload schema or the standard decoders
+    decoded: Option<DecodedValue>,
+}
+
+/// 🧩 **Decoded characteristic value**
+pub enum DecodedValue {
+    Standard(GattValue),
+    Schema(DecodedPayload),
+    Formatted(FormattedValue),
+}
+
+impl Encoder for DecodedValue {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            DecodedValue::Standard(value) => value.encode(env),
+            DecodedValue::Schema(value) => value.encode(env),
+            DecodedValue::Formatted(value) => value.encode(env),
+        }
+    }
+}
+
+/// 📖 **Read result**: the raw value, or `{value, decoded}` when the characteristic is decoded
+enum ReadValue {
+    Raw(BinaryValue),
+    Decoded(BinaryValue, Option<DecodedValue>),
+}
+
+impl Encoder for ReadValue {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            ReadValue::Raw(value) => value.encode(env),
+            ReadValue::Decoded(value, decoded) => (value, decoded).encode(env),
+        }
+    }
+}
+
+/// 📡 **One notification stream per peripheral**, routing each value exactly once
+async fn run_notification_pump(
+    peripheral_weak: Weak<Mutex<PeripheralState>>,
+    mut notifications: Pin<Box<dyn Stream<Item = ValueNotification> + Send>>,
+    pid: LocalPid,
+) {
+    let mut msg_env = OwnedEnv::new();
+    debug!("📡 Started listening for characteristic updates...");
+
+    while let Some(notification) = notifications.next().await {
+        let monotonic_time = monotonic_time_us();
+        let system_time = system_time_us();
+
+        let Some(peripheral_arc) = peripheral_weak.upgrade() else {
+            break;
+        };
+
+        let (peripheral_id, route) = {
+            let state_guard = peripheral_arc.lock().unwrap();
+            let route =
+                state_guard
+                    .subscriptions
+                    .route(&notification.uuid)
+                    .map(|route| match route {
+                        Route::Unique(characteristic) => (
+                            Some(characteristic.service_uuid.to_string()),
+                            state_guard.decode_value(characteristic, &notification.value),
+                        ),
+                        Route::Ambiguous => (
+                            None,
+                            state_guard.decode_standard(&notification.uuid, &notification.value),
+                        ),
+                    });
+            (state_guard.peripheral.id().to_string(), route)
+        };
+
+        let Some((service_uuid, decoded)) = route else {
+            debug!(
+                "🔕 Ignoring value update for unsubscribed UUID: {:?}",
+                notification.uuid
+            );
+            continue;
+        };
+
+        debug!(
+            "📩 Received Value Update: {:?} (UUID: {:?})",
+            notification.value, notification.uuid
+        );
+
+        let meta = ValueChangedMeta {
+            peripheral_id,
+            service_uuid,
+            monotonic_time,
+            system_time,
+            decoded,
+        };
+
+        msg_env
+            .send_and_clear(&pid, |env| {
+                (
+                    atoms::btleplug_characteristic_value_changed(),
+                    notification.uuid.to_string(),
+                    BinaryValue(notification.value),
+                    meta,
+                )
+                    .encode(env)
+            })
+            .ok();
+    }
+
+    warn!("⚠️ Notifications stream ended");
+    if let Some(peripheral_arc) = peripheral_weak.upgrade() {
+        let mut state_guard = peripheral_arc.lock().unwrap();
+        state_guard.subscriptions.clear();
+        state_guard.notification_task = None;
+    }
 }
 
 impl Drop for PeripheralState {
     fn drop(&mut self) {
         debug!("💀 PeripheralResource destructor called.");
+        if let Some(task) = self.notification_task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// 📏 **Read the 0x2904 Presentation Format descriptors** of all characteristics
+async fn cache_presentation_formats(peripheral_arc: &Arc<Mutex<PeripheralState>>, timeout_ms: u64) {
+    let peripheral = peripheral_arc.lock().unwrap().peripheral.clone();
+    let presentation_format_uuid = uuid_from_u16(PRESENTATION_FORMAT_DESCRIPTOR);
+
+    for characteristic in peripheral.characteristics() {
+        let Some(descriptor) = characteristic
+            .descriptors
+            .iter()
+            .find(|d| d.uuid == presentation_format_uuid)
+        else {
+            continue;
+        };
+
+        let format = match timeout(
+            Duration::from_millis(timeout_ms),
+            peripheral.read_descriptor(descriptor),
+        )
+        .await
+        {
+            Ok(Ok(value)) => PresentationFormat::parse(&value),
+            Ok(Err(e)) => {
+                warn!(
+                    "❌ Failed to read presentation format of {:?}: {:?}",
+                    characteristic.uuid, e
+                );
+                None
+            }
+            Err(_) => {
+                warn!(
+                    "⏳ Reading presentation format of {:?} timed out!",
+                    characteristic.uuid
+                );
+                None
+            }
+        };
+
+        if let Some(format) = format {
+            debug!(
+                "📏 Presentation format of {:?}: {:?}",
+                characteristic.uuid, format
+            );
+            peripheral_arc
+                .lock()
+                .unwrap()
+                .presentation_formats
+                .insert((characteristic.service_uuid, characteristic.uuid), format);
+        }
     }
 }
 
@@ -92,6 +430,7 @@ pub async fn discover_services_internal(
             existing_services.iter().map(|s| s.uuid).collect::<Vec<_>>() // Logs discovered service UUIDs
         );
         PeripheralState::set_state(peripheral_arc, PeripheralStateEnum::ServicesDiscovered);
+        cache_presentation_formats(peripheral_arc, timeout_ms).await;
         return true;
     } else {
         debug!("❌ No services found yet for {:?}", peripheral.id());
@@ -110,7 +449,7 @@ pub async fn discover_services_internal(
         .ok()
         .flatten()
     {
-        if let CentralEvent::ServicesAdvertisement { id, .. } = &event {
+        if let (_, CentralEvent::ServicesAdvertisement { id, .. }) = &event {
             if id.to_string() == peripheral.id().to_string() {
                 service_discovered = true;
                 break;
@@ -124,6 +463,8 @@ pub async fn discover_services_internal(
             "✅ Services discovered for peripheral: {:?}",
             peripheral.id()
         );
+        drop(receiver);
+        cache_presentation_formats(peripheral_arc, timeout_ms).await;
         true
     } else {
         warn!("❌ Service discovery timed out for {:?}", peripheral.id());
@@ -132,97 +473,378 @@ pub async fn discover_services_internal(
 }
 
 #[rustler::nif]
-pub fn connect(
-    env: Env,
+pub fn connect<'a>(
+    env: Env<'a>,
     resource: ResourceArc<PeripheralRef>,
     timeout_ms: u64,
-) -> Result<ResourceArc<PeripheralRef>, RustlerError> {
+) -> Result<Reference<'a>, RustlerError> {
     let peripheral_arc = resource.0.clone();
     let env_pid = env.pid();
+    let (reply, op_ref) = OperationReply::new(env, peripheral_arc.lock().unwrap().pid);
 
     RUNTIME.spawn(async move {
-        let (peripheral, pid) = {
-            let state_guard = peripheral_arc.lock().unwrap();
-            (state_guard.peripheral.clone(), state_guard.pid)
-        };
+        reply.send(connect_internal(&peripheral_arc, env_pid, timeout_ms).await);
+    });
 
-        info!(
-            "🔗 Connecting to Peripheral: {:?} (Peripheral Ptr: {:p})",
-            peripheral.id(),
-            &peripheral as *const _
-        );
+    Ok(op_ref)
+}
 
-        PeripheralState::set_state(&peripheral_arc, PeripheralStateEnum::Connecting);
+pub async fn connect_internal(
+    peripheral_arc: &Arc<Mutex<PeripheralState>>,
+    env_pid: LocalPid,
+    timeout_ms: u64,
+) -> Result<(), GattError> {
+    let (peripheral, pid) = {
+        let state_guard = peripheral_arc.lock().unwrap();
+        (state_guard.peripheral.clone(), state_guard.pid)
+    };
 
-        info!(
-            "🔗 Connecting to Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
-            peripheral.id(),
-            env_pid.as_c_arg(),
-            pid.as_c_arg()
-        );
+    info!(
+        "🔗 Connecting to Peripheral: {:?} (Peripheral Ptr: {:p})",
+        peripheral.id(),
+        &peripheral as *const _
+    );
 
-        let mut connected = false;
-        for attempt in 1..=3 {
-            match timeout(Duration::from_millis(timeout_ms), peripheral.connect()).await {
-                Ok(Ok(_)) => {
-                    info!("✅ Connected to peripheral: {:?}", peripheral.id());
-                    connected = true;
-                    info!("🔍 Manually calling discover_services() after connecting, wait 500ms");
-                    tokio::time::sleep(Duration::from_millis(500)).await;
-                    peripheral.discover_services().await;
-                    break;
-                }
-                Ok(Err(e)) => warn!("❌ Connection attempt {} failed: {:?}", attempt, e),
-                Err(_) => warn!("⏳ Connection attempt {} timed out!", attempt),
+    PeripheralState::set_state(peripheral_arc, PeripheralStateEnum::Connecting);
+
+    info!(
+        "🔗 Connecting to Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+        peripheral.id(),
+        env_pid.as_c_arg(),
+        pid.as_c_arg()
+    );
+
+    let mut last_error = GattError::ConnectionFailed;
+    let mut connected = false;
+    for attempt in 1..=3 {
+        match timeout(Duration::from_millis(timeout_ms), peripheral.connect()).await {
+            Ok(Ok(_)) => {
+                info!("✅ Connected to peripheral: {:?}", peripheral.id());
+                connected = true;
+                info!("🔍 Manually calling discover_services() after connecting, wait 500ms");
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                peripheral.discover_services().await;
+                break;
             }
+            Ok(Err(e)) => {
+                warn!("❌ Connection attempt {} failed: {:?}", attempt, e);
+                last_error = GattError::Btleplug(e.to_string());
+            }
+            Err(_) => {
+                warn!("⏳ Connection attempt {} timed out!", attempt);
+                last_error = GattError::Timeout;
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    if !connected {
+        warn!("❌ All connection attempts failed.");
+        PeripheralState::set_state(peripheral_arc, PeripheralStateEnum::Disconnected);
+        return Err(last_error);
+    }
+
+    PeripheralState::set_state(peripheral_arc, PeripheralStateEnum::Connected);
+
+    info!(
+        "🔍 Manually triggering service discovery for peripheral: {:?}",
+        peripheral.id()
+    );
+    if let Err(e) = timeout(
+        Duration::from_millis(timeout_ms),
+        peripheral.discover_services(),
+    )
+    .await
+    {
+        warn!("❌ Service discovery failed: {:?}", e);
+    }
+
+    if !discover_services_internal(peripheral_arc, timeout_ms).await {
+        warn!("⚠️ No services discovered after manual and event-based discovery.");
+    }
 
-            tokio::time::sleep(Duration::from_millis(500)).await;
+    info!(
+        "✅ Returning PeripheralState for {:?} (Peripheral Ptr: {:p})",
+        peripheral.id(),
+        &peripheral as *const _
+    );
+
+    Ok(())
+}
+
+#[rustler::nif]
+pub fn disconnect<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<PeripheralRef>,
+    timeout_ms: u64,
+) -> Result<Reference<'a>, RustlerError> {
+    let peripheral_arc = resource.0.clone();
+    let env_pid = env.pid();
+    let (reply, op_ref) = OperationReply::new(env, peripheral_arc.lock().unwrap().pid);
+
+    RUNTIME.spawn(async move {
+        reply.send(disconnect_internal(&peripheral_arc, env_pid, timeout_ms).await);
+    });
+
+    Ok(op_ref)
+}
+
+async fn disconnect_internal(
+    peripheral_arc: &Arc<Mutex<PeripheralState>>,
+    env_pid: LocalPid,
+    timeout_ms: u64,
+) -> Result<(), GattError> {
+    let (peripheral, pid) = {
+        let state_guard = peripheral_arc.lock().unwrap();
+        (state_guard.peripheral.clone(), state_guard.pid)
+    };
+
+    PeripheralState::set_state(peripheral_arc, PeripheralStateEnum::Disconnecting);
+
+    info!(
+        "🔗 Disconnecting from Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+        peripheral.id(),
+        env_pid.as_c_arg(),
+        pid.as_c_arg()
+    );
+
+    let result = match timeout(Duration::from_millis(timeout_ms), peripheral.disconnect()).await {
+        Ok(Ok(_)) => {
+            info!("✅ Disconnected from peripheral: {:?}", peripheral.id());
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            warn!("❌ Failed to disconnect: {:?}", e);
+            Err(GattError::Btleplug(e.to_string()))
         }
+        Err(_) => {
+            warn!("⏳ Disconnect attempt timed out!");
+            Err(GattError::Timeout)
+        }
+    };
 
-        if !connected {
-            warn!("❌ All connection attempts failed.");
-            PeripheralState::set_state(&peripheral_arc, PeripheralStateEnum::Disconnected);
-            return;
+    match result {
+        Ok(()) => {
+            PeripheralState::stop_notifications(peripheral_arc);
+            PeripheralState::set_state(peripheral_arc, PeripheralStateEnum::Disconnected);
         }
+        Err(_) => {
+            warn!("❌ Disconnect failed.");
+            PeripheralState::set_state(peripheral_arc, PeripheralStateEnum::Connected);
+        }
+    }
 
-        PeripheralState::set_state(&peripheral_arc, PeripheralStateEnum::Connected);
+    result
+}
 
-        info!(
-            "🔍 Manually triggering service discovery for peripheral: {:?}",
-            peripheral.id()
+#[rustler::nif]
+pub fn subscribe<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<PeripheralRef>,
+    characteristic: CharacteristicAddress,
+    timeout_ms: u64,
+) -> Result<Reference<'a>, RustlerError> {
+    let peripheral_arc = resource.0.clone();
+    let env_pid = env.pid();
+    let (reply, op_ref) = OperationReply::new(env, peripheral_arc.lock().unwrap().pid);
+
+    RUNTIME.spawn(async move {
+        reply.send(subscribe_internal(&peripheral_arc, env_pid, characteristic, timeout_ms).await);
+    });
+
+    Ok(op_ref)
+}
+
+async fn subscribe_internal(
+    peripheral_arc: &Arc<Mutex<PeripheralState>>,
+    env_pid: LocalPid,
+    characteristic: CharacteristicAddress,
+    timeout_ms: u64,
+) -> Result<(), GattError> {
+    let (peripheral_clone, pid_clone) = {
+        let state_guard = peripheral_arc.lock().unwrap();
+        (state_guard.peripheral.clone(), state_guard.pid)
+    };
+
+    info!(
+        "🔗 Subscribing to Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+        peripheral_clone.id(),
+        env_pid.as_c_arg(),
+        pid_clone.as_c_arg()
+    );
+
+    ensure_services_discovered(peripheral_arc, timeout_ms).await?;
+
+    info!("🔍 Waiting 2s before checking characteristics...");
+    tokio::time::sleep(Duration::from_millis(2000)).await;
+
+    let char = find_characteristic(&peripheral_clone, &characteristic)?;
+    debug!("🔔 Subscribing to characteristic: {:?}", char.uuid);
+
+    if !char
+        .properties
+        .intersects(CharPropFlags::NOTIFY | CharPropFlags::INDICATE)
+    {
+        debug!(
+            "⚠️ Characteristic {:?} does NOT support notifications!",
+            char.uuid
         );
-        if let Err(e) = timeout(
-            Duration::from_millis(timeout_ms),
-            peripheral.discover_services(),
-        )
-        .await
-        {
-            warn!("❌ Service discovery failed: {:?}", e);
-        }
+        return Err(GattError::NotifyNotSupported);
+    }
 
-        if !discover_services_internal(&peripheral_arc, timeout_ms).await {
-            warn!("⚠️ No services discovered after manual and event-based discovery.");
+    // Route values before the CCCD write so the first notification is not lost
+    PeripheralState::ensure_notification_pump(peripheral_arc, timeout_ms).await?;
+    peripheral_arc
+        .lock()
+        .unwrap()
+        .subscriptions
+        .insert(char.clone());
+
+    match timeout(
+        Duration::from_millis(timeout_ms),
+        peripheral_clone.subscribe(&char),
+    )
+    .await
+    {
+        Ok(Ok(_)) => {
+            info!("✅ Subscribed to characteristic: {:?}", char.uuid);
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            warn!("❌ Failed to subscribe to {:?}: {:?}", char.uuid, e);
+            PeripheralState::remove_subscription(peripheral_arc, &char);
+            Err(GattError::Btleplug(e.to_string()))
         }
+        Err(_) => {
+            warn!("⏳ Subscription to {:?} timed out!", char.uuid);
+            PeripheralState::remove_subscription(peripheral_arc, &char);
+            Err(GattError::Timeout)
+        }
+    }
+}
 
-        info!(
-            "✅ Returning PeripheralState for {:?} (Peripheral Ptr: {:p})",
-            peripheral.id(),
-            &peripheral as *const _
-        );
+#[rustler::nif]
+pub fn unsubscribe<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<PeripheralRef>,
+    characteristic: CharacteristicAddress,
+    timeout_ms: u64,
+) -> Result<Reference<'a>, RustlerError> {
+    let peripheral_arc = resource.0.clone();
+    let env_pid = env.pid();
+    let (reply, op_ref) = OperationReply::new(env, peripheral_arc.lock().unwrap().pid);
+
+    RUNTIME.spawn(async move {
+        reply
+            .send(unsubscribe_internal(&peripheral_arc, env_pid, characteristic, timeout_ms).await);
     });
 
+    Ok(op_ref)
+}
+
+async fn unsubscribe_internal(
+    peripheral_arc: &Arc<Mutex<PeripheralState>>,
+    env_pid: LocalPid,
+    characteristic: CharacteristicAddress,
+    timeout_ms: u64,
+) -> Result<(), GattError> {
+    let (peripheral, pid) = {
+        let state_guard = peripheral_arc.lock().unwrap();
+        (state_guard.peripheral.clone(), state_guard.pid)
+    };
+
+    info!(
+        "🔗 Unsubscribing from Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+        peripheral.id(),
+        env_pid.as_c_arg(),
+        pid.as_c_arg()
+    );
+
+    ensure_services_discovered(peripheral_arc, timeout_ms).await?;
+
+    let char = find_characteristic(&peripheral, &characteristic)?;
+    debug!("🔔 Unsubscribing from characteristic: {:?}", char.uuid);
+
+    if !char
+        .properties
+        .intersects(CharPropFlags::NOTIFY | CharPropFlags::INDICATE)
+    {
+        debug!(
+            "⚠️ Characteristic {:?} does NOT support notifications!",
+            char.uuid
+        );
+        return Err(GattError::NotifyNotSupported);
+    }
+
+    match timeout(
+        Duration::from_millis(timeout_ms),
+        peripheral.unsubscribe(&char),
+    )
+    .await
+    {
+        Ok(Ok(_)) => {
+            info!("✅ Unsubscribed from characteristic: {:?}", char.uuid);
+            PeripheralState::remove_subscription(peripheral_arc, &char);
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            warn!("❌ Failed to unsubscribe from {:?}: {:?}", char.uuid, e);
+            Err(GattError::Btleplug(e.to_string()))
+        }
+        Err(_) => {
+            warn!("⏳ Unsubscribe from {:?} timed out!", char.uuid);
+            Err(GattError::Timeout)
+        }
+    }
+}
+
+/// 🩺 **Turn decoding of standard characteristic values on or off**
+#[rustler::nif]
+pub fn set_value_decoding(
+    resource: ResourceArc<PeripheralRef>,
+    enabled: bool,
+) -> Result<ResourceArc<PeripheralRef>, RustlerError> {
+    info!("🩺 Value decoding enabled: {}", enabled);
+    resource.0.lock().unwrap().decode_values = enabled;
     Ok(resource)
 }
 
+/// 🧩 **Register the payload schema of a characteristic**, `nil` removes it
 #[rustler::nif]
-pub fn disconnect(
+pub fn register_payload_schema(
+    resource: ResourceArc<PeripheralRef>,
+    service_uuid: String,
+    characteristic_uuid: String,
+    schema: Option<PayloadSchema>,
+) -> Result<ResourceArc<PeripheralRef>, RustlerError> {
+    let key = (
+        parse_uuid(&service_uuid)?,
+        parse_uuid(&characteristic_uuid)?,
+    );
+    info!("🧩 Payload schema for {:?}: {:?}", key, schema);
+
+    {
+        let mut state_guard = resource.0.lock().unwrap();
+        match schema {
+            Some(schema) => state_guard.payload_schemas.insert(key, Arc::new(schema)),
+            None => state_guard.payload_schemas.remove(&key),
+        };
+    }
+
+    Ok(resource)
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn read(
     env: Env,
     resource: ResourceArc<PeripheralRef>,
+    characteristic: CharacteristicAddress,
     timeout_ms: u64,
-) -> Result<ResourceArc<PeripheralRef>, RustlerError> {
+) -> Result<ReadValue, RustlerError> {
     let peripheral_arc = resource.0.clone();
-
     let env_pid = env.pid();
+    let (tx, rx) = tokio::sync::oneshot::channel::<Result<ReadValue, GattError>>();
 
     RUNTIME.spawn(async move {
         let (peripheral, pid) = {
@@ -230,259 +852,326 @@ pub fn disconnect(
             (state_guard.peripheral.clone(), state_guard.pid)
         };
 
-        PeripheralState::set_state(&peripheral_arc, PeripheralStateEnum::Disconnecting);
-
         info!(
-            "🔗 Disconnecting from Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+            "📖 Reading {} from Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+            characteristic,
             peripheral.id(),
             env_pid.as_c_arg(),
             pid.as_c_arg()
         );
 
-        let mut disconnected = false;
-        match timeout(Duration::from_millis(timeout_ms), peripheral.disconnect()).await {
-            Ok(Ok(_)) => {
-                info!("✅ Disconnected from peripheral: {:?}", peripheral.id());
-                disconnected = true;
-            }
-            Ok(Err(e)) => warn!("❌ Failed to disconnect: {:?}", e),
-            Err(_) => warn!("⏳ Disconnect attempt timed out!"),
+        if let Err(e) = ensure_services_discovered(&peripheral_arc, timeout_ms).await {
+            let _ = tx.send(Err(e));
+            return;
         }
 
-        if !disconnected {
-            warn!("❌ Disconnect failed.");
-            PeripheralState::set_state(&peripheral_arc, PeripheralStateEnum::Connected);
+        let char = match find_characteristic(&peripheral, &characteristic) {
+            Ok(char) => char,
+            Err(e) => {
+                let _ = tx.send(Err(e));
+                return;
+            }
+        };
+
+        if !char.properties.contains(CharPropFlags::READ) {
+            debug!("⚠️ Characteristic {:?} is NOT readable!", char.uuid);
+            let _ = tx.send(Err(GattError::NotReadable));
             return;
         }
 
-        PeripheralState::set_state(&peripheral_arc, PeripheralStateEnum::Disconnected);
+        let result = match timeout(Duration::from_millis(timeout_ms), peripheral.read(&char)).await
+        {
+            Ok(Ok(value)) => {
+                debug!("✅ Read {:?} from characteristic: {:?}", value, char.uuid);
+                let state_guard = peripheral_arc.lock().unwrap();
+                if state_guard.decodes(&char) {
+                    let decoded = state_guard.decode_value(&char, &value);
+                    Ok(ReadValue::Decoded(BinaryValue(value), decoded))
+                } else {
+                    Ok(ReadValue::Raw(BinaryValue(value)))
+                }
+            }
+            Ok(Err(e)) => {
+                warn!("❌ Failed to read {:?}: {:?}", char.uuid, e);
+                Err(GattError::Btleplug(e.to_string()))
+            }
+            Err(_) => {
+                warn!("⏳ Read of {:?} timed out!", char.uuid);
+                Err(GattError::Timeout)
+            }
+        };
+
+        let _ = tx.send(result);
     });
 
-    Ok(resource)
+    match rx.blocking_recv() {
+        Ok(result) => result.map_err(RustlerError::from),
+        Err(_) => Err(RustlerError::Term(Box::new(
+            "Failed to retrieve result".to_string(),
+        ))),
+    }
 }
 
-#[rustler::nif]
-pub fn subscribe(
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn write(
     env: Env,
     resource: ResourceArc<PeripheralRef>,
-    characteristic_uuid: String,
+    characteristic: CharacteristicAddress,
+    payload: WritePayload,
+    write_type: Atom,
     timeout_ms: u64,
-) -> Result<ResourceArc<PeripheralRef>, RustlerError> {
+) -> Result<Atom, RustlerError> {
+    let write_type = decode_write_type(write_type)?;
     let peripheral_arc = resource.0.clone();
     let env_pid = env.pid();
+    let (tx, rx) = tokio::sync::oneshot::channel::<Result<(), GattError>>();
 
     RUNTIME.spawn(async move {
-        let peripheral_clone = peripheral_arc.lock().unwrap().peripheral.clone();
-        let state_clone = peripheral_arc.lock().unwrap().state;
-        let pid_clone = peripheral_arc.lock().unwrap().pid;
+        let (peripheral, pid) = {
+            let state_guard = peripheral_arc.lock().unwrap();
+            (state_guard.peripheral.clone(), state_guard.pid)
+        };
 
         info!(
-            "🔗 Subscribing to Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
-            peripheral_clone.id(),
+            "✍️ Writing {:?} to {} ({:?}) on Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+            payload,
+            characteristic,
+            write_type,
+            peripheral.id(),
             env_pid.as_c_arg(),
-            pid_clone.as_c_arg()
+            pid.as_c_arg()
         );
 
-        info!(
-            "✅ Returning PeripheralState for {:?} (Peripheral Ptr: {:p})",
-            peripheral_clone.id(),
-            &peripheral_clone as *const _
-        );
+        if let Err(e) = ensure_services_discovered(&peripheral_arc, timeout_ms).await {
+            let _ = tx.send(Err(e));
+            return;
+        }
 
-        if state_clone != PeripheralStateEnum::ServicesDiscovered {
-            warn!("⚠️ Services not yet discovered. Manually triggering discovery...");
-            if let Err(e) = timeout(
-                Duration::from_millis(timeout_ms),
-                peripheral_clone.discover_services(),
-            )
-            .await
-            {
-                warn!("❌ Service discovery failed: {:?}", e);
+        let char = match find_characteristic(&peripheral, &characteristic) {
+            Ok(char) => char,
+            Err(e) => {
+                let _ = tx.send(Err(e));
                 return;
             }
+        };
 
-            RUNTIME.spawn({
-                let peripheral_arc_clone = peripheral_arc.clone();
-                async move {
-                    if !discover_services_internal(&peripheral_arc_clone, timeout_ms).await {
-                        warn!("⚠️ No services discovered, but proceeding with subscription.");
-                    }
-                }
-            });
-        }
-
-        info!("🔍 Waiting 2s before checking characteristics...");
-        tokio::time::sleep(Duration::from_millis(2000)).await;
-
-        let characteristics = peripheral_clone.characteristics();
-        let characteristic = characteristics
-            .iter()
-            .find(|c| c.uuid.to_string() == characteristic_uuid)
-            .cloned();
-
-        if characteristic.is_none() {
-            warn!(
-                "❌ Characteristic {} not found! Available UUIDs: {:?}",
-                characteristic_uuid,
-                characteristics
-                    .iter()
-                    .map(|c| c.uuid.to_string())
-                    .collect::<Vec<_>>()
-            );
+        if let Err(e) = ensure_writable(&char, write_type) {
+            let _ = tx.send(Err(e));
+            return;
         }
 
-        match characteristic {
-            Some(char) => {
-                debug!("🔔 Subscribing to characteristic: {:?}", char.uuid);
-                info!(
-                    "🔔 Found characteristic: {:?}, Properties: {:?}",
-                    char.uuid, char.properties
-                );
-
-                if !char.properties.contains(CharPropFlags::NOTIFY) {
-                    debug!(
-                        "⚠️ Characteristic {:?} does NOT support notifications!",
-                        char.uuid
-                    );
-                    return;
-                }
-
-                match timeout(
-                    Duration::from_millis(timeout_ms),
-                    peripheral_clone.subscribe(&char),
-                )
-                .await
-                {
-                    Ok(Ok(_)) => info!("✅ Subscribed to characteristic: {:?}", char.uuid),
-                    _ => {
-                        warn!("❌ Failed to subscribe to {:?}", char.uuid);
+        let data = match payload {
+            WritePayload::Raw(data) => data,
+            WritePayload::Fields(values) => {
+                let schema = peripheral_arc
+                    .lock()
+                    .unwrap()
+                    .payload_schema(&char)
+                    .cloned();
+                let encoded = match schema {
+                    Some(schema) => schema.encode(&values).map_err(GattError::InvalidPayload),
+                    None => Err(GattError::NoPayloadSchema),
+                };
+                match encoded {
+                    Ok(data) => data,
+                    Err(e) => {
+                        warn!("❌ Cannot encode payload for {:?}: {:?}", char.uuid, e);
+                        let _ = tx.send(Err(e));
                         return;
                     }
                 }
+            }
+        };
 
-                tokio::spawn(async move {
-                    let mut msg_env = OwnedEnv::new();
-
-                    match timeout(
-                        Duration::from_millis(timeout_ms),
-                        peripheral_clone.notifications(),
-                    )
-                    .await
-                    {
-                        Ok(Ok(mut notifications)) => {
-                            debug!("📡 Listening for characteristic updates...");
-
-                            debug!("📡 Started listening for characteristic updates...");
-                            while let Some(notification) = notifications.next().await {
-                                debug!(
-                                    "📩 Received Value Update: {:?} (UUID: {:?})",
-                                    notification.value, notification.uuid
-                                );
-
-                                msg_env
-                                    .send_and_clear(&pid_clone, |env| {
-                                        (
-                                            atoms::btleplug_characteristic_value_changed(),
-                                            notification.uuid.to_string(),
-                                            notification.value.clone(),
-                                        )
-                                            .encode(env)
-                                    })
-                                    .ok();
-                            }
-                            warn!(
-                                "⚠️ Notifications stream ended for UUID: {:?}",
-                                characteristic_uuid
-                            );
-                        }
-                        Ok(Err(e)) => warn!("❌ Subscription failed for {:?}: {:?}", char.uuid, e),
-                        Err(_) => warn!("⏳ Subscription attempt timed out!"),
-                    }
-                });
+        let result = match timeout(
+            Duration::from_millis(timeout_ms),
+            peripheral.write(&char, &data, write_type),
+        )
+        .await
+        {
+            Ok(Ok(_)) => {
+                debug!("✅ Wrote to characteristic: {:?}", char.uuid);
+                Ok(())
             }
-            None => warn!(
-                "❌ Characteristic with UUID {} not found! Available UUIDs: {:?}",
-                characteristic_uuid,
-                characteristics
-                    .iter()
-                    .map(|c| c.uuid.to_string())
-                    .collect::<Vec<_>>()
-            ),
-        }
+            Ok(Err(e)) => {
+                warn!("❌ Failed to write {:?}: {:?}", char.uuid, e);
+                Err(GattError::Btleplug(e.to_string()))
+            }
+            Err(_) => {
+                warn!("⏳ Write to {:?} timed out!", char.uuid);
+                Err(GattError::Timeout)
+            }
+        };
+
+        let _ = tx.send(result);
     });
 
-    Ok(resource)
+    match rx.blocking_recv() {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(RustlerError::Term(Box::new(
+            "Failed to retrieve result".to_string(),
+        ))),
+    }
 }
 
-#[rustler::nif]
-pub fn unsubscribe(
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn read_descriptor(
     env: Env,
     resource: ResourceArc<PeripheralRef>,
+    service_uuid: String,
     characteristic_uuid: String,
+    descriptor_uuid: String,
     timeout_ms: u64,
-) -> Result<ResourceArc<PeripheralRef>, RustlerError> {
+) -> Result<BinaryValue, RustlerError> {
     let peripheral_arc = resource.0.clone();
     let env_pid = env.pid();
+    let (tx, rx) = tokio::sync::oneshot::channel::<Result<BinaryValue, GattError>>();
 
     RUNTIME.spawn(async move {
-        let (peripheral, state, pid) = {
+        let (peripheral, pid) = {
             let state_guard = peripheral_arc.lock().unwrap();
-            (
-                state_guard.peripheral.clone(),
-                state_guard.state,
-                state_guard.pid,
-            )
+            (state_guard.peripheral.clone(), state_guard.pid)
         };
 
         info!(
-            "🔗 Unsubscribing from Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+            "📖 Reading descriptor {} of {}/{} from Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+            descriptor_uuid,
+            service_uuid,
+            characteristic_uuid,
             peripheral.id(),
             env_pid.as_c_arg(),
             pid.as_c_arg()
         );
 
-        if state != PeripheralStateEnum::ServicesDiscovered {
-            warn!("⚠️ Services not yet discovered. Waiting for service event...");
-            let discovered = discover_services_internal(&peripheral_arc, timeout_ms).await;
-            if !discovered {
-                warn!("❌ Cannot proceed with unsubscribe. No services discovered.");
+        if let Err(e) = ensure_services_discovered(&peripheral_arc, timeout_ms).await {
+            let _ = tx.send(Err(e));
+            return;
+        }
+
+        let descriptor = match find_descriptor(
+            &peripheral,
+            &service_uuid,
+            &characteristic_uuid,
+            &descriptor_uuid,
+        ) {
+            Ok(descriptor) => descriptor,
+            Err(e) => {
+                let _ = tx.send(Err(e));
                 return;
             }
+        };
+
+        let result = match timeout(
+            Duration::from_millis(timeout_ms),
+            peripheral.read_descriptor(&descriptor),
+        )
+        .await
+        {
+            Ok(Ok(value)) => {
+                debug!("✅ Read {:?} from descriptor: {:?}", value, descriptor.uuid);
+                Ok(BinaryValue(value))
+            }
+            Ok(Err(e)) => {
+                warn!("❌ Failed to read descriptor {:?}: {:?}", descriptor.uuid, e);
+                Err(GattError::Btleplug(e.to_string()))
+            }
+            Err(_) => {
+                warn!("⏳ Read of descriptor {:?} timed out!", descriptor.uuid);
+                Err(GattError::Timeout)
+            }
+        };
+
+        let _ = tx.send(result);
+    });
+
+    match rx.blocking_recv() {
+        Ok(result) => result.map_err(RustlerError::from),
+        Err(_) => Err(RustlerError::Term(Box::new(
+            "Failed to retrieve result".to_string(),
+        ))),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn write_descriptor(
+    env: Env,
+    resource: ResourceArc<PeripheralRef>,
+    service_uuid: String,
+    characteristic_uuid: String,
+    descriptor_uuid: String,
+    data: Binary,
+    timeout_ms: u64,
+) -> Result<Atom, RustlerError> {
+    let data = data.as_slice().to_vec();
+    let peripheral_arc = resource.0.clone();
+    let env_pid = env.pid();
+    let (tx, rx) = tokio::sync::oneshot::channel::<Result<(), GattError>>();
+
+    RUNTIME.spawn(async move {
+        let (peripheral, pid) = {
+            let state_guard = peripheral_arc.lock().unwrap();
+            (state_guard.peripheral.clone(), state_guard.pid)
+        };
+
+        info!(
+            "✍️ Writing {:?} to descriptor {} of {}/{} on Peripheral: {:?}, caller pid: {:?}, state pid: {:?}",
+            data,
+            descriptor_uuid,
+            service_uuid,
+            characteristic_uuid,
+            peripheral.id(),
+            env_pid.as_c_arg(),
+            pid.as_c_arg()
+        );
+
+        if let Err(e) = ensure_services_discovered(&peripheral_arc, timeout_ms).await {
+            let _ = tx.send(Err(e));
+            return;
         }
 
-        let characteristics = peripheral.characteristics();
-        let characteristic = characteristics
-            .iter()
-            .find(|c| c.uuid.to_string() == characteristic_uuid)
-            .cloned();
-
-        match characteristic {
-            Some(char) => {
-                debug!("🔔 Unsubscribing from characteristic: {:?}", char.uuid);
-
-                if !char.properties.contains(CharPropFlags::NOTIFY) {
-                    debug!(
-                        "⚠️ Characteristic {:?} does NOT support notifications!",
-                        char.uuid
-                    );
-                    return;
-                }
+        let descriptor = match find_descriptor(
+            &peripheral,
+            &service_uuid,
+            &characteristic_uuid,
+            &descriptor_uuid,
+        ) {
+            Ok(descriptor) => descriptor,
+            Err(e) => {
+                let _ = tx.send(Err(e));
+                return;
+            }
+        };
 
-                match timeout(
-                    Duration::from_millis(timeout_ms),
-                    peripheral.unsubscribe(&char),
-                )
-                .await
-                {
-                    Ok(Ok(_)) => info!("✅ Unsubscribed from characteristic: {:?}", char.uuid),
-                    _ => {
-                        warn!("❌ Failed to unsubscribe from {:?}", char.uuid);
-                    }
-                }
+        let result = match timeout(
+            Duration::from_millis(timeout_ms),
+            peripheral.write_descriptor(&descriptor, &data),
+        )
+        .await
+        {
+            Ok(Ok(_)) => {
+                debug!("✅ Wrote to descriptor: {:?}", descriptor.uuid);
+                Ok(())
             }
-            None => info!("⚠️ Characteristic not found: {}", characteristic_uuid),
-        }
+            Ok(Err(e)) => {
+                warn!("❌ Failed to write descriptor {:?}: {:?}", descriptor.uuid, e);
+                Err(GattError::Btleplug(e.to_string()))
+            }
+            Err(_) => {
+                warn!("⏳ Write to descriptor {:?} timed out!", descriptor.uuid);
+                Err(GattError::Timeout)
+            }
+        };
+
+        let _ = tx.send(result);
     });
 
-    Ok(resource)
+    match rx.blocking_recv() {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(RustlerError::Term(Box::new(
+            "Failed to retrieve result".to_string(),
+        ))),
+    }
 }
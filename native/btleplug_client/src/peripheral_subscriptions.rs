@@ -0,0 +1,164 @@
+use btleplug::api::Characteristic;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 🔔 **Subscribed characteristics by (service, characteristic) UUID**
+///
+/// Notifications only carry the characteristic UUID, so the number of subscribed
+/// services per characteristic UUID decides whether a value can be routed to one
+/// service or is ambiguous.
+#[derive(Debug, Default)]
+pub struct Subscriptions {
+    characteristics: HashMap<(Uuid, Uuid), Characteristic>,
+    services_per_uuid: HashMap<Uuid, usize>,
+}
+
+/// 📬 **Where a notification belongs**
+#[derive(Debug, PartialEq)]
+pub enum Route<'a> {
+    /// Exactly one subscribed service has the characteristic
+    Unique(&'a Characteristic),
+    /// Several subscribed services share the characteristic UUID
+    Ambiguous,
+}
+
+impl Subscriptions {
+    pub fn insert(&mut self, characteristic: Characteristic) {
+        let key = (characteristic.service_uuid, characteristic.uuid);
+        if self.characteristics.insert(key, characteristic).is_none() {
+            *self.services_per_uuid.entry(key.1).or_insert(0) += 1;
+        }
+    }
+
+    /// Returns `true` once no service has the characteristic UUID subscribed anymore
+    pub fn remove(&mut self, service_uuid: &Uuid, uuid: &Uuid) -> bool {
+        if self
+            .characteristics
+            .remove(&(*service_uuid, *uuid))
+            .is_some()
+        {
+            if let Some(count) = self.services_per_uuid.get_mut(uuid) {
+                *count -= 1;
+                if *count == 0 {
+                    self.services_per_uuid.remove(uuid);
+                }
+            }
+        }
+        !self.services_per_uuid.contains_key(uuid)
+    }
+
+    pub fn route(&self, uuid: &Uuid) -> Option<Route<'_>> {
+        match self.services_per_uuid.get(uuid)? {
+            1 => self
+                .characteristics
+                .values()
+                .find(|characteristic| characteristic.uuid == *uuid)
+                .map(Route::Unique),
+            _ => Some(Route::Ambiguous),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.characteristics.clear();
+        self.services_per_uuid.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.characteristics.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use btleplug::api::bleuuid::uuid_from_u16;
+    use btleplug::api::CharPropFlags;
+    use std::collections::BTreeSet;
+
+    fn characteristic(service: u16, uuid: u16) -> Characteristic {
+        Characteristic {
+            uuid: uuid_from_u16(uuid),
+            service_uuid: uuid_from_u16(service),
+            properties: CharPropFlags::NOTIFY,
+            descriptors: BTreeSet::new(),
+        }
+    }
+
+    #[test]
+    fn routes_a_single_subscription() {
+        let mut subscriptions = Subscriptions::default();
+        assert!(subscriptions.is_empty());
+        assert_eq!(subscriptions.route(&uuid_from_u16(0x2A37)), None);
+
+        subscriptions.insert(characteristic(0x180D, 0x2A37));
+        assert!(!subscriptions.is_empty());
+        assert_eq!(
+            subscriptions.route(&uuid_from_u16(0x2A37)),
+            Some(Route::Unique(&characteristic(0x180D, 0x2A37)))
+        );
+        assert_eq!(subscriptions.route(&uuid_from_u16(0x2A19)), None);
+    }
+
+    #[test]
+    fn shared_uuid_stays_routed_until_the_last_service_unsubscribes() {
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.insert(characteristic(0xFFE0, 0xFFE1));
+        subscriptions.insert(characteristic(0xFFF0, 0xFFE1));
+        // Subscribing twice to the same characteristic does not count twice
+        subscriptions.insert(characteristic(0xFFF0, 0xFFE1));
+
+        let uuid = uuid_from_u16(0xFFE1);
+        assert_eq!(subscriptions.route(&uuid), Some(Route::Ambiguous));
+
+        assert!(!subscriptions.remove(&uuid_from_u16(0xFFE0), &uuid));
+        assert_eq!(
+            subscriptions.route(&uuid),
+            Some(Route::Unique(&characteristic(0xFFF0, 0xFFE1)))
+        );
+        assert!(!subscriptions.is_empty());
+
+        assert!(subscriptions.remove(&uuid_from_u16(0xFFF0), &uuid));
+        assert_eq!(subscriptions.route(&uuid), None);
+        assert!(subscriptions.is_empty());
+    }
+
+    #[test]
+    fn removing_an_unknown_service_keeps_the_routing() {
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.insert(characteristic(0xFFE0, 0xFFE1));
+
+        let uuid = uuid_from_u16(0xFFE1);
+        assert!(!subscriptions.remove(&uuid_from_u16(0xFFF0), &uuid));
+        assert!(subscriptions.route(&uuid).is_some());
+    }
+
+    #[test]
+    fn pump_stops_once_the_last_subscription_is_gone() {
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.insert(characteristic(0x180D, 0x2A37));
+        subscriptions.insert(characteristic(0x180F, 0x2A19));
+
+        subscriptions.remove(&uuid_from_u16(0x180D), &uuid_from_u16(0x2A37));
+        assert!(!subscriptions.is_empty());
+        subscriptions.remove(&uuid_from_u16(0x180F), &uuid_from_u16(0x2A19));
+        assert!(subscriptions.is_empty());
+    }
+
+    #[test]
+    fn disconnect_clears_all_routing() {
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.insert(characteristic(0xFFE0, 0xFFE1));
+        subscriptions.insert(characteristic(0xFFF0, 0xFFE1));
+
+        subscriptions.clear();
+        assert!(subscriptions.is_empty());
+        assert_eq!(subscriptions.route(&uuid_from_u16(0xFFE1)), None);
+
+        // A fresh subscription after reconnecting starts counting from zero
+        subscriptions.insert(characteristic(0xFFE0, 0xFFE1));
+        assert!(matches!(
+            subscriptions.route(&uuid_from_u16(0xFFE1)),
+            Some(Route::Unique(_))
+        ));
+    }
+}
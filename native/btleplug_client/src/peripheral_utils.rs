@@ -0,0 +1,498 @@
+use crate::atoms;
+use crate::payload_schema::PayloadError;
+use crate::peripheral::{PeripheralState, PeripheralStateEnum};
+
+use log::{debug, warn};
+use rustler::env::SavedTerm;
+use rustler::sys::ErlNifTimeUnit;
+use rustler::{
+    Atom, Binary, Decoder, Encoder, Env, Error as RustlerError, LocalPid, NewBinary, NifResult,
+    OwnedEnv, Reference, Term,
+};
+
+use btleplug::api::{
+    CharPropFlags, Characteristic, Descriptor, Peripheral as ApiPeripheral, Service, WriteType,
+};
+use btleplug::platform::Peripheral;
+use uuid::Uuid;
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{timeout, Duration};
+
+/// ✅ **Raw bytes encoded as an Erlang binary** (`Vec<u8>` encodes as a list)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryValue(pub Vec<u8>);
+
+impl Encoder for BinaryValue {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let mut binary = NewBinary::new(env, self.0.len());
+        binary.as_mut_slice().copy_from_slice(&self.0);
+        Binary::from(binary).encode(env)
+    }
+}
+
+impl<'a> Decoder<'a> for BinaryValue {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let binary: Binary = term.decode()?;
+        Ok(BinaryValue(binary.as_slice().to_vec()))
+    }
+}
+
+/// 🗺️ **Build an atom-keyed map** for structs that can't derive `NifMap`
+pub fn atom_map<'a>(env: Env<'a>, fields: Vec<(Atom, Term<'a>)>) -> Term<'a> {
+    fields
+        .into_iter()
+        .fold(Term::map_new(env), |map, (key, value)| {
+            map.map_put(key, value).unwrap_or(map)
+        })
+}
+
+/// ⏱️ **Erlang monotonic time in µs**, comparable with `System.monotonic_time(:microsecond)`
+pub fn monotonic_time_us() -> i64 {
+    unsafe { rustler::sys::enif_monotonic_time(ErlNifTimeUnit::ERL_NIF_USEC) }
+}
+
+/// 🕰️ **Wall-clock time in µs since the Unix epoch**
+pub fn system_time_us() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as i64)
+        .unwrap_or_default()
+}
+
+/// ❌ **Errors returned to Elixir as `{:error, reason}`**
+#[derive(Debug, Clone)]
+pub enum GattError {
+    ServiceDiscoveryFailed,
+    ServiceNotFound,
+    CharacteristicNotFound,
+    DescriptorNotFound,
+    AmbiguousCharacteristic(Vec<(String, String)>),
+    NotReadable,
+    NotWritable,
+    NotifyNotSupported,
+    ConnectionFailed,
+    InvalidWriteType,
+    NoPayloadSchema,
+    InvalidPayload(PayloadError),
+    PeripheralNotFound,
+    Timeout,
+    Btleplug(String),
+}
+
+impl Encoder for GattError {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            GattError::ServiceDiscoveryFailed => atoms::service_discovery_failed().encode(env),
+            GattError::ServiceNotFound => atoms::service_not_found().encode(env),
+            GattError::CharacteristicNotFound => atoms::characteristic_not_found().encode(env),
+            GattError::DescriptorNotFound => atoms::descriptor_not_found().encode(env),
+            GattError::AmbiguousCharacteristic(candidates) => {
+                (atoms::ambiguous_characteristic(), candidates.clone()).encode(env)
+            }
+            GattError::NotReadable => atoms::not_readable().encode(env),
+            GattError::NotWritable => atoms::not_writable().encode(env),
+            GattError::NotifyNotSupported => atoms::notify_not_supported().encode(env),
+            GattError::ConnectionFailed => atoms::connection_failed().encode(env),
+            GattError::InvalidWriteType => atoms::invalid_write_type().encode(env),
+            GattError::NoPayloadSchema => atoms::no_payload_schema().encode(env),
+            GattError::InvalidPayload(e) => e.encode(env),
+            GattError::PeripheralNotFound => atoms::peripheral_not_found().encode(env),
+            GattError::Timeout => atoms::timeout().encode(env),
+            GattError::Btleplug(msg) => (atoms::btleplug_error(), msg.clone()).encode(env),
+        }
+    }
+}
+
+impl From<GattError> for RustlerError {
+    fn from(err: GattError) -> Self {
+        RustlerError::Term(Box::new(err))
+    }
+}
+
+/// 📬 **Correlates an async operation with the reference returned to Elixir**
+///
+/// Sends `{:btleplug_op_result, ref, :ok | {:error, reason}}` to the owning pid.
+pub struct OperationReply {
+    pid: LocalPid,
+    ref_env: OwnedEnv,
+    reference: SavedTerm,
+}
+
+impl OperationReply {
+    pub fn new(env: Env<'_>, pid: LocalPid) -> (Self, Reference<'_>) {
+        let op_ref = env.make_ref();
+        let ref_env = OwnedEnv::new();
+        let reference = ref_env.save(op_ref);
+
+        (
+            OperationReply {
+                pid,
+                ref_env,
+                reference,
+            },
+            op_ref,
+        )
+    }
+
+    /// Sends `{tag, ref, payload}`, the reference stays valid for later messages
+    fn send_tagged<T: Encoder>(&self, tag: Atom, payload: &T) {
+        let mut msg_env = OwnedEnv::new();
+        match msg_env.send_and_clear(&self.pid, |env| {
+            let reference = self
+                .ref_env
+                .run(|ref_env| self.reference.load(ref_env).in_env(env));
+            (tag, reference, payload).encode(env)
+        }) {
+            Ok(_) => debug!("✅ Sent operation message: {:?}", tag),
+            Err(e) => debug!("⚠️ Failed to send operation message: {:?}", e),
+        }
+    }
+
+    /// 🚥 **Intermediate step of a multi-phase operation**, as `{:btleplug_op_progress, ref, phase}`
+    pub fn progress<T: Encoder>(&self, phase: T) {
+        self.send_tagged(atoms::btleplug_op_progress(), &phase);
+    }
+
+    pub fn send(self, result: Result<(), GattError>) {
+        debug!("📬 Operation result: {:?}", result);
+        self.send_tagged(atoms::btleplug_op_result(), &OperationResult(result));
+    }
+}
+
+/// `:ok | {:error, reason}` as sent in `btleplug_op_result`
+struct OperationResult(Result<(), GattError>);
+
+impl Encoder for OperationResult {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match &self.0 {
+            Ok(()) => atoms::ok().encode(env),
+            Err(e) => (atoms::error(), e).encode(env),
+        }
+    }
+}
+
+/// 🔍 **Make sure the GATT table is available before looking up characteristics**
+pub async fn ensure_services_discovered(
+    peripheral_arc: &Arc<Mutex<PeripheralState>>,
+    timeout_ms: u64,
+) -> Result<(), GattError> {
+    let (peripheral, state) = {
+        let state_guard = peripheral_arc.lock().unwrap();
+        (state_guard.peripheral.clone(), state_guard.state)
+    };
+
+    if state == PeripheralStateEnum::ServicesDiscovered || !peripheral.services().is_empty() {
+        return Ok(());
+    }
+
+    warn!("⚠️ Services not yet discovered. Manually triggering discovery...");
+    match timeout(
+        Duration::from_millis(timeout_ms),
+        peripheral.discover_services(),
+    )
+    .await
+    {
+        Ok(Ok(_)) => {
+            PeripheralState::set_state(peripheral_arc, PeripheralStateEnum::ServicesDiscovered);
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            warn!("❌ Service discovery failed: {:?}", e);
+            Err(GattError::ServiceDiscoveryFailed)
+        }
+        Err(_) => {
+            warn!("⏳ Service discovery timed out for {:?}", peripheral.id());
+            Err(GattError::Timeout)
+        }
+    }
+}
+
+/// 📍 **Characteristic address**: `"char-uuid"` or `{service_uuid | nil, "char-uuid"}`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CharacteristicAddress {
+    pub service_uuid: Option<String>,
+    pub uuid: String,
+}
+
+impl<'a> Decoder<'a> for CharacteristicAddress {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if let Ok(uuid) = term.decode::<String>() {
+            return Ok(CharacteristicAddress {
+                service_uuid: None,
+                uuid,
+            });
+        }
+
+        let (service_uuid, uuid): (Option<String>, String) = term.decode()?;
+        Ok(CharacteristicAddress { service_uuid, uuid })
+    }
+}
+
+impl fmt::Display for CharacteristicAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.service_uuid {
+            Some(service_uuid) => write!(f, "{}/{}", service_uuid, self.uuid),
+            None => write!(f, "{}", self.uuid),
+        }
+    }
+}
+
+fn uuid_matches(uuid: &Uuid, wanted: &str) -> bool {
+    uuid.to_string().eq_ignore_ascii_case(wanted)
+}
+
+/// 🔎 **Resolve a Characteristic via `peripheral.services()`**
+///
+/// Without a service UUID the characteristic UUID has to be unique on the device,
+/// otherwise `{:ambiguous_characteristic, candidates}` is returned.
+pub fn find_characteristic(
+    peripheral: &Peripheral,
+    address: &CharacteristicAddress,
+) -> Result<Characteristic, GattError> {
+    find_service_characteristic(&peripheral.services(), address)
+}
+
+/// 🔎 **Resolve a Characteristic in a GATT table**, see `find_characteristic`
+pub fn find_service_characteristic(
+    services: &BTreeSet<Service>,
+    address: &CharacteristicAddress,
+) -> Result<Characteristic, GattError> {
+    if let Some(service_uuid) = &address.service_uuid {
+        if !services.iter().any(|s| uuid_matches(&s.uuid, service_uuid)) {
+            warn!(
+                "❌ Service {} not found! Available UUIDs: {:?}",
+                service_uuid,
+                services
+                    .iter()
+                    .map(|s| s.uuid.to_string())
+                    .collect::<Vec<_>>()
+            );
+            return Err(GattError::ServiceNotFound);
+        }
+    }
+
+    let mut candidates: Vec<Characteristic> = services
+        .iter()
+        .filter(|s| {
+            address
+                .service_uuid
+                .as_deref()
+                .is_none_or(|service_uuid| uuid_matches(&s.uuid, service_uuid))
+        })
+        .flat_map(|s| s.characteristics.iter())
+        .filter(|c| uuid_matches(&c.uuid, &address.uuid))
+        .cloned()
+        .collect();
+
+    match candidates.len() {
+        1 => {
+            let char = candidates.remove(0);
+            debug!(
+                "🔔 Found characteristic: {:?} (service {:?}), Properties: {:?}",
+                char.uuid, char.service_uuid, char.properties
+            );
+            Ok(char)
+        }
+        0 => {
+            warn!(
+                "❌ Characteristic {} not found! Available UUIDs: {:?}",
+                address,
+                services
+                    .iter()
+                    .flat_map(|s| s.characteristics.iter())
+                    .map(|c| c.uuid.to_string())
+                    .collect::<Vec<_>>()
+            );
+            Err(GattError::CharacteristicNotFound)
+        }
+        _ => {
+            warn!(
+                "⚠️ Characteristic {} is ambiguous, found in services: {:?}",
+                address,
+                candidates
+                    .iter()
+                    .map(|c| c.service_uuid.to_string())
+                    .collect::<Vec<_>>()
+            );
+            Err(GattError::AmbiguousCharacteristic(
+                candidates
+                    .iter()
+                    .map(|c| (c.service_uuid.to_string(), c.uuid.to_string()))
+                    .collect(),
+            ))
+        }
+    }
+}
+
+/// 🔎 **Find a Descriptor by (service, characteristic, descriptor) UUID triple**
+pub fn find_descriptor(
+    peripheral: &Peripheral,
+    service_uuid: &str,
+    characteristic_uuid: &str,
+    descriptor_uuid: &str,
+) -> Result<Descriptor, GattError> {
+    let characteristic = find_characteristic(
+        peripheral,
+        &CharacteristicAddress {
+            service_uuid: Some(service_uuid.to_string()),
+            uuid: characteristic_uuid.to_string(),
+        },
+    )?;
+
+    characteristic
+        .descriptors
+        .iter()
+        .find(|d| uuid_matches(&d.uuid, descriptor_uuid))
+        .cloned()
+        .ok_or_else(|| {
+            warn!(
+                "❌ Descriptor {} not found! Available UUIDs: {:?}",
+                descriptor_uuid,
+                characteristic
+                    .descriptors
+                    .iter()
+                    .map(|d| d.uuid.to_string())
+                    .collect::<Vec<_>>()
+            );
+            GattError::DescriptorNotFound
+        })
+}
+
+/// ✍️ **Map `:with_response` / `:without_response` to a btleplug `WriteType`**
+pub fn decode_write_type(write_type: Atom) -> Result<WriteType, GattError> {
+    if write_type == atoms::with_response() {
+        Ok(WriteType::WithResponse)
+    } else if write_type == atoms::without_response() {
+        Ok(WriteType::WithoutResponse)
+    } else {
+        Err(GattError::InvalidWriteType)
+    }
+}
+
+/// ✅ **Check the characteristic flags allow the requested write type**
+pub fn ensure_writable(
+    characteristic: &Characteristic,
+    write_type: WriteType,
+) -> Result<(), GattError> {
+    let required = match write_type {
+        WriteType::WithResponse => CharPropFlags::WRITE,
+        WriteType::WithoutResponse => CharPropFlags::WRITE_WITHOUT_RESPONSE,
+    };
+
+    if characteristic.properties.contains(required) {
+        Ok(())
+    } else {
+        debug!(
+            "⚠️ Characteristic {:?} does NOT support {:?}! Properties: {:?}",
+            characteristic.uuid, write_type, characteristic.properties
+        );
+        Err(GattError::NotWritable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use btleplug::api::bleuuid::uuid_from_u16;
+
+    const BATTERY: u16 = 0x180F;
+    const DEVICE_INFO: u16 = 0x180A;
+    const SHARED: u16 = 0x2A19;
+    const MODEL: u16 = 0x2A24;
+
+    fn services(table: &[(u16, &[u16])]) -> BTreeSet<Service> {
+        table
+            .iter()
+            .map(|(service, characteristics)| Service {
+                uuid: uuid_from_u16(*service),
+                primary: true,
+                characteristics: characteristics
+                    .iter()
+                    .map(|uuid| Characteristic {
+                        uuid: uuid_from_u16(*uuid),
+                        service_uuid: uuid_from_u16(*service),
+                        properties: CharPropFlags::empty(),
+                        descriptors: BTreeSet::new(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn address(service: Option<u16>, uuid: u16) -> CharacteristicAddress {
+        CharacteristicAddress {
+            service_uuid: service.map(|service| uuid_from_u16(service).to_string()),
+            uuid: uuid_from_u16(uuid).to_string(),
+        }
+    }
+
+    fn resolve(address: CharacteristicAddress) -> Result<(Uuid, Uuid), GattError> {
+        let table = services(&[(BATTERY, &[SHARED]), (DEVICE_INFO, &[SHARED, MODEL])]);
+        find_service_characteristic(&table, &address).map(|c| (c.service_uuid, c.uuid))
+    }
+
+    #[test]
+    fn resolves_a_unique_characteristic_without_its_service() {
+        assert_eq!(
+            resolve(address(None, MODEL)).unwrap(),
+            (uuid_from_u16(DEVICE_INFO), uuid_from_u16(MODEL))
+        );
+
+        let mut upper = address(None, MODEL);
+        upper.uuid = upper.uuid.to_uppercase();
+        assert!(resolve(upper).is_ok());
+    }
+
+    #[test]
+    fn service_qualifies_a_shared_characteristic() {
+        assert_eq!(
+            resolve(address(Some(BATTERY), SHARED)).unwrap(),
+            (uuid_from_u16(BATTERY), uuid_from_u16(SHARED))
+        );
+
+        let mut upper = address(Some(DEVICE_INFO), SHARED);
+        upper.service_uuid = upper.service_uuid.map(|uuid| uuid.to_uppercase());
+        assert_eq!(
+            resolve(upper).unwrap(),
+            (uuid_from_u16(DEVICE_INFO), uuid_from_u16(SHARED))
+        );
+    }
+
+    #[test]
+    fn shared_characteristic_without_service_is_ambiguous() {
+        let Err(GattError::AmbiguousCharacteristic(mut candidates)) =
+            resolve(address(None, SHARED))
+        else {
+            panic!("expected an ambiguous characteristic");
+        };
+        candidates.sort();
+
+        let shared = uuid_from_u16(SHARED).to_string();
+        let mut expected = vec![
+            (uuid_from_u16(BATTERY).to_string(), shared.clone()),
+            (uuid_from_u16(DEVICE_INFO).to_string(), shared),
+        ];
+        expected.sort();
+        assert_eq!(candidates, expected);
+    }
+
+    #[test]
+    fn reports_unknown_services_and_characteristics() {
+        assert!(matches!(
+            resolve(address(Some(0xFFFF), SHARED)),
+            Err(GattError::ServiceNotFound)
+        ));
+        assert!(matches!(
+            resolve(address(None, 0x2A00)),
+            Err(GattError::CharacteristicNotFound)
+        ));
+        assert!(matches!(
+            resolve(address(Some(BATTERY), MODEL)),
+            Err(GattError::CharacteristicNotFound)
+        ));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::assigned_numbers::unit_name;
+use crate::atoms;
+use crate::gatt_values::{float, sfloat};
+use crate::payload_schema::FieldValue;
+use crate::peripheral_utils::atom_map;
+
+use rustler::{Atom, Binary, Encoder, Env, Term};
+
+/// 📏 **Characteristic Presentation Format descriptor** (0x2904)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PresentationFormat {
+    pub format: u8,
+    pub exponent: i8,
+    pub unit: u16,
+    pub namespace: u8,
+    pub description: u16,
+}
+
+/// 📏 **Value scaled with the presentation format**, encodes as `%{value, unit}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormattedValue {
+    pub value: FieldValue,
+    pub unit: Option<&'static str>,
+}
+
+impl Encoder for FormattedValue {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let unit = self
+            .unit
+            .and_then(|unit| Atom::from_str(env, unit).ok())
+            .encode(env);
+        atom_map(
+            env,
+            vec![
+                (atoms::value(), self.value.encode(env)),
+                (atoms::unit(), unit),
+            ],
+        )
+    }
+}
+
+impl PresentationFormat {
+    pub fn parse(data: &[u8]) -> Option<PresentationFormat> {
+        match data {
+            [format, exponent, unit_lo, unit_hi, namespace, desc_lo, desc_hi, ..] => {
+                Some(PresentationFormat {
+                    format: *format,
+                    exponent: *exponent as i8,
+                    unit: u16::from_le_bytes([*unit_lo, *unit_hi]),
+                    namespace: *namespace,
+                    description: u16::from_le_bytes([*desc_lo, *desc_hi]),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// (bits, signed) of the integer formats, 12-bit values take two octets
+    fn integer_layout(&self) -> Option<(u32, bool)> {
+        match self.format {
+            0x04 => Some((8, false)),
+            0x05 => Some((12, false)),
+            0x06 => Some((16, false)),
+            0x07 => Some((24, false)),
+            0x08 => Some((32, false)),
+            0x09 => Some((48, false)),
+            0x0A => Some((64, false)),
+            0x0C => Some((8, true)),
+            0x0D => Some((12, true)),
+            0x0E => Some((16, true)),
+            0x0F => Some((24, true)),
+            0x10 => Some((32, true)),
+            0x11 => Some((48, true)),
+            0x12 => Some((64, true)),
+            _ => None,
+        }
+    }
+
+    /// 🔢 **Scale a raw value**, `None` for non-numeric formats, short values or
+    /// IEEE-11073 special values
+    pub fn apply(&self, data: &[u8]) -> Option<FormattedValue> {
+        let value = if let Some((bits, signed)) = self.integer_layout() {
+            let bytes = bits.div_ceil(8) as usize;
+            let mut buf = [0u8; 8];
+            buf[..bytes].copy_from_slice(data.get(..bytes)?);
+            // Shifting the value to the top and back drops the unused high bits
+            let shift = 64 - bits;
+            let raw = if signed {
+                ((i64::from_le_bytes(buf) << shift) >> shift) as i128
+            } else {
+                ((u64::from_le_bytes(buf) << shift) >> shift) as i128
+            };
+
+            // value = raw × 10^exponent
+            match self.exponent {
+                0 => FieldValue::Int(raw),
+                exponent if exponent < 0 => {
+                    FieldValue::Float(raw as f64 / 10f64.powi(-exponent as i32))
+                }
+                exponent => FieldValue::Float(raw as f64 * 10f64.powi(exponent as i32)),
+            }
+        } else {
+            match self.format {
+                0x01 => FieldValue::Int((*data.first()? & 0x01) as i128),
+                0x14 => {
+                    FieldValue::Float(f32::from_le_bytes(data.get(..4)?.try_into().ok()?) as f64)
+                }
+                0x15 => FieldValue::Float(f64::from_le_bytes(data.get(..8)?.try_into().ok()?)),
+                0x16 => {
+                    FieldValue::Float(sfloat(u16::from_le_bytes(data.get(..2)?.try_into().ok()?))?)
+                }
+                0x17 => {
+                    FieldValue::Float(float(u32::from_le_bytes(data.get(..4)?.try_into().ok()?))?)
+                }
+                _ => return None,
+            }
+        };
+
+        Some(FormattedValue {
+            value,
+            unit: unit_name(self.unit),
+        })
+    }
+}
+
+/// 🔍 **Scale a value with a raw 0x2904 descriptor**, `nil` when it can't be applied
+#[rustler::nif]
+pub fn apply_presentation_format(descriptor: Binary, value: Binary) -> Option<FormattedValue> {
+    PresentationFormat::parse(descriptor.as_slice())?.apply(value.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(format: u8, exponent: i8) -> PresentationFormat {
+        PresentationFormat {
+            format,
+            exponent,
+            unit: 0x2700,
+            namespace: 0x01,
+            description: 0,
+        }
+    }
+
+    fn value(format: &PresentationFormat, data: &[u8]) -> Option<FieldValue> {
+        format.apply(data).map(|formatted| formatted.value)
+    }
+
+    #[test]
+    fn uint12_masks_the_high_nibble() {
+        let uint12 = format(0x05, 0);
+        assert_eq!(value(&uint12, &[0x34, 0x02]), Some(FieldValue::Int(0x234)));
+        assert_eq!(value(&uint12, &[0xFF, 0xFF]), Some(FieldValue::Int(0xFFF)));
+        assert_eq!(value(&uint12, &[0x34]), None);
+    }
+
+    #[test]
+    fn sint12_is_sign_extended() {
+        let sint12 = format(0x0D, 0);
+        assert_eq!(value(&sint12, &[0xFF, 0x0F]), Some(FieldValue::Int(-1)));
+        assert_eq!(value(&sint12, &[0x00, 0xF8]), Some(FieldValue::Int(-2048)));
+        assert_eq!(value(&sint12, &[0xFF, 0x07]), Some(FieldValue::Int(2047)));
+        assert_eq!(
+            value(&format(0x0D, -1), &[0x06, 0xFF]),
+            Some(FieldValue::Float(-25.0))
+        );
+    }
+
+    #[test]
+    fn whole_octet_integers_keep_their_width() {
+        assert_eq!(
+            value(&format(0x0E, 0), &[0x00, 0x80]),
+            Some(FieldValue::Int(-32768))
+        );
+        assert_eq!(
+            value(&format(0x0A, 0), &[0xFF; 8]),
+            Some(FieldValue::Int(u64::MAX as i128))
+        );
+        assert_eq!(
+            value(&format(0x12, 0), &[0xFF; 8]),
+            Some(FieldValue::Int(-1))
+        );
+    }
+}
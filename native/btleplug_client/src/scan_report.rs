@@ -0,0 +1,135 @@
+use crate::advertisement::Advertisement;
+use crate::atoms;
+use crate::peripheral_utils::{system_time_us, BinaryValue};
+use crate::RUNTIME;
+
+use log::{debug, warn};
+use rustler::{Atom, Encoder, LocalPid, NifMap, OwnedEnv};
+
+use btleplug::api::PeripheralProperties;
+use std::collections::HashMap;
+use std::sync::{Mutex, Weak};
+use tokio::time::{interval, Duration, MissedTickBehavior};
+
+/// Latest properties of a device within the current report window
+#[derive(Debug, Clone)]
+pub struct ScanReportEntry {
+    pub properties: PeripheralProperties,
+    /// µs since the Unix epoch
+    pub last_seen: i64,
+}
+
+/// 🗂️ **One device of a `btleplug_scan_report`**: the `Advertisement` fields plus
+/// the peripheral id and when it was last seen
+#[derive(NifMap)]
+#[rustler(encode)]
+struct ScanReportItem {
+    id: String,
+    last_seen: i64,
+    address: String,
+    address_type: Option<Atom>,
+    local_name: Option<String>,
+    tx_power_level: Option<i16>,
+    rssi: Option<i16>,
+    manufacturer_data: HashMap<u16, BinaryValue>,
+    service_data: HashMap<String, BinaryValue>,
+    services: Vec<String>,
+    class: Option<u32>,
+}
+
+impl ScanReportItem {
+    fn new(peripheral_id: String, entry: ScanReportEntry) -> Self {
+        let advertisement = Advertisement::from(&entry.properties);
+        ScanReportItem {
+            id: peripheral_id,
+            last_seen: entry.last_seen,
+            address: advertisement.address,
+            address_type: advertisement.address_type,
+            local_name: advertisement.local_name,
+            tx_power_level: advertisement.tx_power_level,
+            rssi: advertisement.rssi,
+            manufacturer_data: advertisement.manufacturer_data,
+            service_data: advertisement.service_data,
+            services: advertisement.services,
+            class: advertisement.class,
+        }
+    }
+}
+
+/// 🗂️ **Collects advertisements of a window into one `btleplug_scan_report`**
+#[derive(Debug, Default)]
+pub struct ScanReport {
+    entries: HashMap<String, ScanReportEntry>,
+}
+
+impl ScanReport {
+    /// Newer advertisements of a device overwrite older ones in the same window
+    pub fn record(&mut self, peripheral_id: &str, properties: PeripheralProperties) {
+        self.entries.insert(
+            peripheral_id.to_string(),
+            ScanReportEntry {
+                properties,
+                last_seen: system_time_us(),
+            },
+        );
+    }
+
+    pub fn drain(&mut self) -> Vec<(String, ScanReportEntry)> {
+        self.entries.drain().collect()
+    }
+
+    /// ⏲️ **Send a report every `interval_ms`** until the central is dropped
+    pub fn spawn_reporter(report: Weak<Mutex<ScanReport>>, pid: LocalPid, interval_ms: u64) {
+        RUNTIME.spawn(async move {
+            let mut ticker = interval(Duration::from_millis(interval_ms.max(1)));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+
+                let Some(report) = report.upgrade() else {
+                    debug!("📴 Scan report task closed");
+                    break;
+                };
+
+                let entries: Vec<ScanReportItem> = report
+                    .lock()
+                    .unwrap()
+                    .drain()
+                    .into_iter()
+                    .map(|(id, entry)| ScanReportItem::new(id, entry))
+                    .collect();
+                if entries.is_empty() {
+                    continue;
+                }
+
+                let mut msg_env = OwnedEnv::new();
+                if let Err(e) = msg_env.send_and_clear(&pid, |env| {
+                    (atoms::btleplug_scan_report(), entries).encode(env)
+                }) {
+                    warn!("⚠️ Failed to send scan report: {:?}", e);
+                }
+            }
+        });
+    }
+}
+
+/// Record the advertisement if the central is in report mode,
+/// `true` means the caller must not send its own message
+pub fn record_for_report(
+    report: &Option<Weak<Mutex<ScanReport>>>,
+    peripheral_id: &str,
+    properties: Option<&PeripheralProperties>,
+) -> bool {
+    let Some(report) = report.as_ref().and_then(Weak::upgrade) else {
+        return false;
+    };
+
+    if let Some(properties) = properties {
+        report
+            .lock()
+            .unwrap()
+            .record(peripheral_id, properties.clone());
+    }
+    true
+}
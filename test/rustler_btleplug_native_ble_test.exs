@@ -4,6 +4,7 @@ defmodule RustlerBtleplug.NativeBleTest do
 
   @ble_peripheral_name "Pressure"
   @ble_characteristic_uuid "61d20a90-71a1-11ea-ab12-0800200c9a66"
+  @cccd_uuid "00002902-0000-1000-8000-00805f9b34fb"
 
   @doc """
   Some Standard characteristic UUIDs
@@ -19,6 +20,54 @@ defmodule RustlerBtleplug.NativeBleTest do
     assert is_reference(resource)
   end
 
+  test "BLE adapter selection" do
+    assert [%{index: 0, info: info} | _] = adapters = Native.list_adapters()
+
+    assert is_reference(Native.create_central(self(), %{adapter: 0}))
+    assert is_reference(Native.create_central(self(), %{adapter: info}))
+
+    missing = length(adapters)
+
+    assert {:error, {:adapter_not_found, ^missing, [^info | _]}} =
+             Native.create_central(self(), %{adapter: missing})
+
+    assert {:error, {:adapter_not_found, "no such radio", _}} =
+             Native.create_central(self(), %{adapter: "no such radio"})
+  end
+
+  test "BLE multiple adapters" do
+    adapters = Native.list_adapters()
+
+    central =
+      Native.create_central(self(), %{adapters: :all})
+      |> Native.start_scan(1000)
+
+    assert_receive {:btleplug_scan_started, _msg}
+    assert_receive {:btleplug_peripheral_discovered, uuid, %{adapter: adapter}}, 2000
+    adapters_idx = Enum.map(adapters, & &1.index)
+    assert adapter in adapters_idx
+    assert central |> Native.adapter_rssi(uuid) |> Map.keys() |> Enum.all?(&(&1 in adapters_idx))
+
+    assert {:error, {:adapter_not_found, "no such radio", _}} =
+             Native.create_central(self(), %{adapters: [0, "no such radio"]})
+  end
+
+  test "BLE adapter power state" do
+    central = Native.create_central()
+
+    assert Native.adapter_state(central) in [:powered_on, :powered_off, :unknown]
+    assert :powered_on = Native.wait_for_adapter_state(central, :powered_on, 2000)
+    assert {:error, :timeout} = Native.wait_for_adapter_state(central, :powered_off, 100)
+  end
+
+  test "BLE connect by address" do
+    central = Native.create_central()
+
+    ref = Native.connect_by_address(central, "00:00:00:00:00:00", 500, 500)
+    assert_receive {:btleplug_op_progress, ^ref, :scanning}
+    assert_receive {:btleplug_op_result, ^ref, {:error, :peripheral_not_found}}, 2000
+  end
+
   test "BLE default scanning" do
     # {:ok, ble_resource} = Native.create_central()
     resource =
@@ -83,6 +132,70 @@ defmodule RustlerBtleplug.NativeBleTest do
     end
   end
 
+  test "BLE continuous scanning until stop_scan" do
+    central =
+      Native.create_central()
+      |> Native.start_scan(100, %{continuous: true})
+
+    assert_receive {:btleplug_scan_started, %{session_id: session_id, continuous: true}}
+
+    refute_receive {:btleplug_scan_stopped, _msg}, 500
+    assert %{scanning: true, session_id: ^session_id} = Native.scan_status(central)
+
+    central |> Native.stop_scan()
+
+    assert_receive {:btleplug_scan_stopped, %{session_id: ^session_id, reason: :stopped}}
+    assert %{scanning: false, session_id: nil} = Native.scan_status(central)
+  end
+
+  test "BLE restarted scan is not stopped by the stale timer" do
+    central =
+      Native.create_central()
+      |> Native.start_scan(300)
+
+    assert_receive {:btleplug_scan_started, %{session_id: first}}
+
+    central |> Native.start_scan(2000)
+
+    assert_receive {:btleplug_scan_stopped, %{session_id: ^first, reason: :replaced}}
+    assert_receive {:btleplug_scan_started, %{session_id: second}}
+    refute_receive {:btleplug_scan_stopped, %{session_id: ^second}}, 800
+  end
+
+  test "BLE throttled peripheral updates are counted" do
+    central =
+      Native.create_central(self(), %{update_throttle: %{min_interval_ms: 5000, rssi_delta: 10}})
+      |> Native.start_scan(1000)
+
+    assert_receive {:btleplug_scan_started, _msg}
+    assert_receive {:btleplug_scan_stopped, _msg}, 2000
+
+    assert %{total_suppressed: total, suppressed: suppressed} =
+             Native.update_throttle_stats(central)
+
+    assert total == suppressed |> Map.values() |> Enum.sum()
+
+    assert is_reference(central |> Native.set_update_throttle(nil))
+  end
+
+  test "BLE batch scan reports" do
+    Native.create_central(self(), %{scan_report_interval_ms: 200})
+    |> Native.start_scan(1000)
+
+    assert_receive {:btleplug_scan_started, _msg}
+    assert_receive {:btleplug_scan_report, [%{id: _id, last_seen: _, rssi: rssi} | _]}, 1000
+    assert is_integer(rssi) or is_nil(rssi)
+    refute_received {:btleplug_peripheral_discovered, _uuid, _props}
+  end
+
+  test "BLE legacy advertisement map format" do
+    Native.create_central(self(), %{advertisement_format: :map})
+    |> Native.start_scan()
+
+    assert_receive {:btleplug_scan_started, _msg}
+    assert_receive {:btleplug_peripheral_discovered, _uuid, %{"local_name" => _, "rssi" => _}}, 1000
+  end
+
   test "BLE fail to find unknown peripheral" do
     # {:ok, ble_resource} = Native.create_central()
     {status, msg} =
@@ -114,7 +227,8 @@ defmodule RustlerBtleplug.NativeBleTest do
 
         assert is_binary(peripheral_id)
         assert is_reference(peripheral_resource)
-        assert is_map(properties)
+        assert %RustlerBtleplug.Advertisement{rssi: rssi} = properties
+        assert is_integer(rssi) or is_nil(rssi)
     after
       2000 -> flunk("Did not receive :btleplug_peripheral_discovered message")
     end
@@ -142,10 +256,15 @@ defmodule RustlerBtleplug.NativeBleTest do
           central_resource
           #|> Native.start_scan()
           |> Native.find_peripheral_by_name(@ble_peripheral_name)
-          |> Native.connect()
-          |> Native.subscribe(@ble_characteristic_uuid)
 
         assert is_reference(peripheral_resource)
+
+        connect_ref = Native.connect(peripheral_resource)
+        assert_receive {:btleplug_op_result, ^connect_ref, :ok}, timeout
+
+        subscribe_ref = Native.subscribe(peripheral_resource, @ble_characteristic_uuid)
+        assert_receive {:btleplug_op_result, ^subscribe_ref, :ok}, timeout
+
         assert_receive {:btleplug_peripheral_connected, _msg},
                        timeout,
                        "No :btleplug_peripheral_connected received"
@@ -158,10 +277,13 @@ defmodule RustlerBtleplug.NativeBleTest do
         # assert_receive {:btleplug_services_advertisement, _msg}, timeout, "No :btleplug_services_advertisement received"
         # assert_receive {:btleplug_service_data_advertisement, _msg}, timeout, "No :btleplug_service_data_advertisement received"
         # assert_receive {:btleplug_peripheral_connected, _msg}, timeout, "No :btleplug_peripheral_connected received"
-        assert_receive {:btleplug_characteristic_value_changed, _uuid, _value},
+        assert_receive {:btleplug_characteristic_value_changed, _uuid, value, meta},
                        timeout,
                        "No :btleplug_characteristic_value_changed received"
 
+        assert is_binary(value)
+        assert %{peripheral_id: _, service_uuid: _, monotonic_time: _, system_time: _} = meta
+
         Process.sleep(1000)
 
         state_graph = central_resource |> Native.get_adapter_state_graph()
@@ -175,7 +297,136 @@ defmodule RustlerBtleplug.NativeBleTest do
   end
 
 
+  test "BLE read characteristic" do
+    central_resource =
+      Native.create_central()
+      |> Native.start_scan()
+
+    assert_receive {:btleplug_scan_started, _msg}, 1000
+    Process.sleep(2000)
+
+    peripheral_resource =
+      central_resource
+      |> Native.find_peripheral_by_name(@ble_peripheral_name)
+
+    connect_ref = Native.connect(peripheral_resource)
+    assert_receive {:btleplug_op_result, ^connect_ref, :ok}, 5000
+
+    assert {:error, :characteristic_not_found} =
+             Native.read(peripheral_resource, "00000000-0000-0000-0000-000000000000")
+
+    value = Native.read(peripheral_resource, @ble_characteristic_uuid, 5000)
+    assert is_binary(value) or match?({:error, :not_readable}, value)
+  end
+
+  test "BLE read and write descriptors" do
+    central_resource =
+      Native.create_central()
+      |> Native.start_scan()
+
+    assert_receive {:btleplug_scan_started, _msg}, 1000
+    Process.sleep(2000)
+
+    peripheral_resource =
+      central_resource
+      |> Native.find_peripheral_by_name(@ble_peripheral_name)
+
+    connect_ref = Native.connect(peripheral_resource)
+    assert_receive {:btleplug_op_result, ^connect_ref, :ok}, 5000
+
+    %{peripherals: peripherals} = Native.get_adapter_state_map(central_resource)
+
+    [{service, characteristic}] =
+      for %{services: services} <- peripherals,
+          %{uuid: service_uuid, characteristics: characteristics} <- services,
+          %{uuid: @ble_characteristic_uuid} = characteristic <- characteristics,
+          do: {service_uuid, characteristic}
+
+    assert %RustlerBtleplug.CharacteristicInfo{descriptors: descriptors} = characteristic
+    assert Enum.all?(descriptors, &match?(%RustlerBtleplug.DescriptorInfo{uuid: _}, &1))
+
+    assert {:error, :descriptor_not_found} =
+             Native.read_descriptor(
+               peripheral_resource,
+               service,
+               @ble_characteristic_uuid,
+               "00000000-0000-0000-0000-000000000000"
+             )
+
+    if Enum.any?(descriptors, &(&1.uuid == @cccd_uuid)) do
+      assert <<_::binary-size(2)>> =
+               Native.read_descriptor(
+                 peripheral_resource,
+                 service,
+                 @ble_characteristic_uuid,
+                 @cccd_uuid,
+                 5000
+               )
+
+      assert :ok =
+               Native.write_descriptor(
+                 peripheral_resource,
+                 service,
+                 @ble_characteristic_uuid,
+                 @cccd_uuid,
+                 <<0, 0>>,
+                 5000
+               )
+    end
+  end
+
+  test "BLE write characteristic" do
+    central_resource =
+      Native.create_central()
+      |> Native.start_scan()
+
+    assert_receive {:btleplug_scan_started, _msg}, 1000
+    Process.sleep(2000)
+
+    peripheral_resource =
+      central_resource
+      |> Native.find_peripheral_by_name(@ble_peripheral_name)
+
+    connect_ref = Native.connect(peripheral_resource)
+    assert_receive {:btleplug_op_result, ^connect_ref, :ok}, 5000
+
+    assert {:error, :invalid_write_type} =
+             Native.write(peripheral_resource, @ble_characteristic_uuid, <<1>>, :sometimes)
+
+    assert {:error, :characteristic_not_found} =
+             Native.write(peripheral_resource, "00000000-0000-0000-0000-000000000000", <<1>>)
+
+    for write_type <- [:with_response, :without_response] do
+      result =
+        Native.write(peripheral_resource, @ble_characteristic_uuid, <<1>>, write_type, 5000)
+
+      assert result in [:ok, {:error, :not_writable}]
+    end
+  end
+
+
+
+
+  test "BLE subscribe to unknown characteristic reports error" do
+    central_resource =
+      Native.create_central()
+      |> Native.start_scan()
+
+    assert_receive {:btleplug_scan_started, _msg}, 1000
+    Process.sleep(2000)
+
+    peripheral_resource =
+      Native.find_peripheral_by_name(central_resource, @ble_peripheral_name)
+
+    connect_ref = Native.connect(peripheral_resource)
+    assert is_reference(connect_ref)
+    assert_receive {:btleplug_op_result, ^connect_ref, :ok}, 5000
+
+    subscribe_ref = Native.subscribe(peripheral_resource, "00000000-0000-0000-0000-000000000000")
 
+    assert_receive {:btleplug_op_result, ^subscribe_ref, {:error, :characteristic_not_found}},
+                   5000
+  end
 
   # @tag timeout: :infinity
   # test "Create GATT peripheral" do
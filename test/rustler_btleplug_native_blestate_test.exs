@@ -56,8 +56,12 @@ defmodule RustlerBtleplug.NativeBlestateTest do
     assert_receive {:btleplug_peripheral_discovered, _msg, _props}
 
     peripheral_resource = Native.find_peripheral_by_name(central_resource, @ble_peripheral_name)
-    |> Native.connect()
-    |> Native.subscribe(@ble_characteristic_uuid)
+
+    connect_ref = Native.connect(peripheral_resource)
+    assert_receive {:btleplug_op_result, ^connect_ref, :ok}, 5000
+
+    subscribe_ref = Native.subscribe(peripheral_resource, @ble_characteristic_uuid)
+    assert_receive {:btleplug_op_result, ^subscribe_ref, :ok}, 5000
 
     Process.sleep(2000)
 
@@ -67,6 +71,19 @@ defmodule RustlerBtleplug.NativeBlestateTest do
     assert is_map(state_map)
     IO.inspect(state_map)
 
+    characteristics =
+      for %{services: services} <- state_map.peripherals,
+          %{characteristics: characteristics} <- services,
+          characteristic <- characteristics,
+          do: characteristic
+
+    assert characteristics != []
+
+    for %RustlerBtleplug.CharacteristicInfo{descriptors: descriptors} <- characteristics do
+      assert is_list(descriptors)
+      assert Enum.all?(descriptors, &match?(%RustlerBtleplug.DescriptorInfo{uuid: _}, &1))
+    end
+
     assert_receive {:btleplug_scan_stopped, _msg}
   end
 
@@ -17,4 +17,237 @@ defmodule RustlerBtleplug.NativeTest do
     assert is_map(map), "Expected map"
   end
 
+  test "Decode iBeacon" do
+    uuid = Base.decode16!("E2C56DB5DFFB48D2B060D0F5A71096E0")
+
+    assert %{
+             type: :ibeacon,
+             uuid: "e2c56db5-dffb-48d2-b060-d0f5a71096e0",
+             major: 1,
+             minor: 2,
+             measured_power: -59
+           } = Native.decode_beacon(0x004C, <<0x02, 0x15>> <> uuid <> <<0, 1, 0, 2, 0xC5>>)
+  end
+
+  test "Decode AltBeacon" do
+    id = Base.decode16!("E2C56DB5DFFB48D2B060D0F5A71096E0")
+
+    assert %{type: :altbeacon, company_id: 0x0118, major: 7, minor: 8, measured_power: -60} =
+             Native.decode_beacon(0x0118, <<0xBE, 0xAC>> <> id <> <<0, 7, 0, 8, 0xC4, 0>>)
+  end
+
+  test "Decode Eddystone frames" do
+    assert %{
+             type: :eddystone_uid,
+             tx_power: -18,
+             namespace: "00112233445566778899",
+             instance: "aabbccddeeff"
+           } =
+             Native.decode_beacon(
+               "feaa",
+               <<0x00, 0xEE>> <> Base.decode16!("00112233445566778899AABBCCDDEEFF")
+             )
+
+    assert %{type: :eddystone_url, url: "https://goo.gl/S6zT6P"} =
+             Native.decode_beacon("feaa", <<0x10, 0xEE, 0x03>> <> "goo.gl/S6zT6P")
+
+    assert %{type: :eddystone_url, url: "http://www.example.com/"} =
+             Native.decode_beacon("feaa", <<0x10, 0xEE, 0x00>> <> "example" <> <<0x00>>)
+
+    assert %{
+             type: :eddystone_tlm,
+             battery_mv: 3000,
+             temperature: 21.5,
+             adv_count: 10,
+             uptime_ms: 5000
+           } =
+             Native.decode_beacon(
+               "0000feaa-0000-1000-8000-00805f9b34fb",
+               <<0x20, 0x00, 3000::16, 0x15, 0x80, 10::32, 50::32>>
+             )
+  end
+
+  test "Decode BTHome v2" do
+    assert %{
+             format: :bthome,
+             encrypted: false,
+             packet_id: 9,
+             measurements: [
+               %{name: :temperature, value: 25.06, unit: "°C"},
+               %{name: :humidity, value: 50.55, unit: "%"},
+               %{name: :battery, value: 93, unit: "%"},
+               %{name: :button, value: :long_press}
+             ]
+           } =
+             Native.decode_bthome(
+               <<0x40, 0x00, 9, 0x02, 0xCA, 0x09, 0x03, 0xBF, 0x13, 0x01, 93, 0x3A, 0x04>>
+             )
+  end
+
+  test "Decode encrypted BTHome v2" do
+    data = Base.decode16!("41A47266C95F730011223378237214")
+    bindkey = "231d39c1d7cc1ab1aee224cd096db932"
+
+    assert %{
+             encrypted: true,
+             measurements: [
+               %{name: :temperature, value: 25.06},
+               %{name: :humidity, value: 50.55}
+             ]
+           } = Native.decode_bthome(data, "54:48:E6:8F:80:A5", bindkey)
+
+    assert {:error, :missing_bindkey} = Native.decode_bthome(data, "54:48:E6:8F:80:A5")
+
+    assert {:error, :decryption_failed} =
+             Native.decode_bthome(data, "54:48:E6:8F:80:A6", bindkey)
+
+    # Neither 16 raw bytes nor 32 hex digits
+    for bad <- [String.duplicate("é", 16), "+" <> String.slice(bindkey, 1..-1//1), "short"] do
+      assert {:error, :invalid_bindkey} = Native.decode_bthome(data, "54:48:E6:8F:80:A5", bad)
+    end
+  end
+
+  test "Decode non-beacon payloads" do
+    assert Native.decode_beacon(0x004C, <<0x10, 0x05, 0x01>>) == nil
+    assert Native.decode_beacon("180d", <<0x00, 0xEE>>) == nil
+  end
+
+  test "Look up assigned numbers" do
+    assert {:service, "Heart Rate"} = Native.lookup_assigned_number("180d")
+
+    assert {:characteristic, "Battery Level"} =
+             Native.lookup_assigned_number("00002a19-0000-1000-8000-00805f9b34fb")
+
+    assert {:descriptor, "Client Characteristic Configuration"} =
+             Native.lookup_assigned_number("2902")
+
+    assert {:company, "Apple, Inc."} = Native.lookup_assigned_number(0x004C)
+    assert {:appearance, "Keyboard"} = Native.lookup_assigned_number(0x03C1, :appearance)
+    assert {:appearance, "Watch"} = Native.lookup_assigned_number(0x00C5, :appearance)
+    assert Native.lookup_assigned_number("180d", :characteristic) == nil
+    assert Native.lookup_assigned_number("6e400001-b5a3-f393-e0a9-e50e24dcca9e") == nil
+  end
+
+  test "Decode standard characteristic values" do
+    assert %{type: :battery_level, battery_level: 87} =
+             Native.decode_characteristic_value("2a19", <<87>>)
+
+    # 16-bit heart rate, contact detected, energy expended, two RR intervals
+    assert %{
+             type: :heart_rate_measurement,
+             heart_rate: 300,
+             sensor_contact: true,
+             energy_expended_kj: 12,
+             rr_intervals_ms: [1000.0, 500.0]
+           } =
+             Native.decode_characteristic_value(
+               "2a37",
+               <<0x1F, 300::little-16, 12::little-16, 1024::little-16, 512::little-16>>
+             )
+
+    # 36.5 °C as IEEE-11073 FLOAT (mantissa 365, exponent -1), taken in the ear
+    assert %{
+             type: :temperature_measurement,
+             temperature: 36.5,
+             unit: "°C",
+             timestamp: {{2024, 5, 17}, {8, 30, 0}},
+             temperature_type: :ear
+           } =
+             Native.decode_characteristic_value(
+               "2a1c",
+               <<0x06, 365::little-24, -1::signed-8, 2024::little-16, 5, 17, 8, 30, 0, 3>>
+             )
+
+    assert %{
+             type: :blood_pressure_measurement,
+             systolic: 120.0,
+             diastolic: 80.0,
+             mean_arterial_pressure: nil,
+             unit: "mmHg",
+             pulse_rate: 72.0
+           } =
+             Native.decode_characteristic_value(
+               "2a35",
+               <<0x04, 120::little-16, 80::little-16, 0x07FF::little-16, 72::little-16>>
+             )
+
+    assert %{type: :weight_measurement, weight: 70.0, unit: "kg"} =
+             Native.decode_characteristic_value("2a9d", <<0x00, 14_000::little-16>>)
+
+    assert Native.decode_characteristic_value("2a37", <<0x01, 0x2C>>) == nil
+    assert Native.decode_characteristic_value("2a00", "name") == nil
+  end
+
+  test "Decode and encode payload schemas" do
+    schema = [
+      {:temperature, :i16le, scale: 0.01},
+      {:pressure, :u32be},
+      {:status, {:bitfield, [:charging, :low_battery]}},
+      {:label, {:string, 4}},
+      {:ratio, :f32le}
+    ]
+
+    data = <<-1234::little-signed-16, 101_325::big-32, 0b01, "ab", 0, 0, 0.5::little-float-32>>
+
+    assert %{
+             temperature: -12.34,
+             pressure: 101_325,
+             status: %{charging: true, low_battery: false},
+             label: "ab",
+             ratio: 0.5
+           } = Native.decode_payload(schema, data)
+
+    assert Native.encode_payload(schema, %{
+             temperature: -12.34,
+             pressure: 101_325,
+             status: [:charging],
+             label: "ab",
+             ratio: 0.5
+           }) == data
+
+    assert Native.decode_payload(schema, binary_part(data, 0, 3)) == nil
+
+    assert {:error, {:missing_field, :ratio}} =
+             Native.encode_payload(schema, %{temperature: 1, pressure: 1, status: [], label: ""})
+
+    assert {:error, {:invalid_field, :pressure}} =
+             Native.encode_payload(schema, %{
+               temperature: 1,
+               pressure: -1,
+               status: [],
+               label: "",
+               ratio: 0
+             })
+
+    assert {:error, {:invalid_schema, _}} = Native.decode_payload([{:x, :u24le}], <<>>)
+    assert {:error, {:invalid_schema, _}} = Native.decode_payload([{:x, :""}], <<>>)
+    assert {:error, {:invalid_schema, _}} = Native.decode_payload([{:x, :"ü16le"}], <<>>)
+    assert {:error, {:invalid_schema, _}} = Native.decode_payload([{:x, {:bytes, 513}}], <<>>)
+
+    assert Native.decode_payload([{:x, {:string, 512}}], String.duplicate("a", 512)) == %{
+             x: String.duplicate("a", 512)
+           }
+  end
+
+  test "Apply presentation formats" do
+    # sint16, exponent -2, °C (0x272F), SIG namespace
+    descriptor = <<0x0E, -2::signed-8, 0x272F::little-16, 0x01, 0x0000::little-16>>
+
+    assert %{value: 21.5, unit: :degree_celsius} =
+             Native.apply_presentation_format(descriptor, <<2150::little-signed-16>>)
+
+    # uint8 percentage without exponent stays an integer
+    assert %{value: 87, unit: :percentage} =
+             Native.apply_presentation_format(<<0x04, 0, 0x27AD::little-16, 1, 0, 0>>, <<87>>)
+
+    # sint12 keeps only the low 12 bits, sign-extended
+    sint12 = <<0x0D, 0, 0x2700::little-16, 1, 0, 0>>
+
+    assert %{value: -1, unit: :unitless} =
+             Native.apply_presentation_format(sint12, <<0xFF, 0x0F>>)
+
+    assert Native.apply_presentation_format(descriptor, <<1>>) == nil
+    assert Native.apply_presentation_format(<<0x19, 0, 0x2700::little-16, 1, 0, 0>>, "a") == nil
+    assert {:unit, "degree_celsius"} = Native.lookup_assigned_number(0x272F, :unit)
+  end
 end